@@ -44,16 +44,20 @@ pub fn criterion_benchmark(c: &mut Criterion) {
     advent_of_code_2022::day13::part1::tests::benchmark(c);
     advent_of_code_2022::day13::part2_solve1::tests::benchmark(c);
     advent_of_code_2022::day13::part2_solve2::tests::benchmark(c);
+    advent_of_code_2022::day13::part2_solve3::tests::benchmark(c);
 
     advent_of_code_2022::day14::part1::tests::benchmark(c);
     advent_of_code_2022::day14::part2_solve1::tests::benchmark(c);
     advent_of_code_2022::day14::part2_solve2::tests::benchmark(c);
+    advent_of_code_2022::day14::part2_solve3::tests::benchmark(c);
 
     advent_of_code_2022::day15::part1::tests::benchmark(c);
-    advent_of_code_2022::day15::part2::tests::benchmark(c);
+    advent_of_code_2022::day15::part2_solve1::tests::benchmark(c);
+    advent_of_code_2022::day15::part2_solve2::tests::benchmark(c);
     
     advent_of_code_2022::day16::part1::tests::benchmark(c);
-    advent_of_code_2022::day16::part2::tests::benchmark(c);
+    advent_of_code_2022::day16::part2_solve1::tests::benchmark(c);
+    advent_of_code_2022::day16::part2_solve2::tests::benchmark(c);
 
     advent_of_code_2022::day17::part1::tests::benchmark(c);
     advent_of_code_2022::day17::part2::tests::benchmark(c);
@@ -64,11 +68,14 @@ pub fn criterion_benchmark(c: &mut Criterion) {
     advent_of_code_2022::day19::part1::tests::benchmark(c);
     advent_of_code_2022::day19::part2::tests::benchmark(c);
 
-    advent_of_code_2022::day20::part1::tests::benchmark(c);
-    advent_of_code_2022::day20::part2::tests::benchmark(c);
+    advent_of_code_2022::day20::part1_solve1::tests::benchmark(c);
+    advent_of_code_2022::day20::part1_solve2::tests::benchmark(c);
+    advent_of_code_2022::day20::part2_solve1::tests::benchmark(c);
+    advent_of_code_2022::day20::part2_solve2::tests::benchmark(c);
 
     advent_of_code_2022::day21::part1::tests::benchmark(c);
-    advent_of_code_2022::day21::part2::tests::benchmark(c);
+    advent_of_code_2022::day21::part2_solve1::tests::benchmark(c);
+    advent_of_code_2022::day21::part2_solve2::tests::benchmark(c);
 
     advent_of_code_2022::day25::part1::tests::benchmark(c);
     advent_of_code_2022::day25::part2::tests::benchmark(c);
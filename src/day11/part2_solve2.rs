@@ -1,6 +1,7 @@
-use std::{vec, hash::{Hash, Hasher}, collections::{hash_map::DefaultHasher, HashMap}};
+use std::{vec, hash::{Hash, Hasher}, collections::hash_map::DefaultHasher};
 use itertools::Itertools;
 use super::lib::*;
+use crate::util::cycle::{CycleDetector, Strategy};
 
 fn hash_monkey_items(monkeys: &Vec<Monkey>) -> u64 {
     let mut hasher = DefaultHasher::new();
@@ -27,13 +28,11 @@ fn find_top_two(items: &Vec<usize>) -> (usize, usize) {
 
 fn solve(mut monkeys: Vec<Monkey>) -> usize {
     let mut num_items_inspected = vec![0; monkeys.len()];
-    let mut top_two_per_round = Vec::new();
-    let mut seen_states = HashMap::new();
+    let mut cycle_detector = CycleDetector::new(Strategy::HashMap);
     let worry_lcm: usize = monkeys.iter().map(|m| m.divisor).product();
 
-    let mut round = 0;
     let total_rounds = 10000;
-    while round < total_rounds {
+    for _round in 0..total_rounds {
         for i in 0..monkeys.len() {
             let monkey = &mut monkeys[i];
             let inspection_result = monkey.items
@@ -60,36 +59,11 @@ fn solve(mut monkeys: Vec<Monkey>) -> usize {
 
         // Check if we've seen this state before after a round
         let hash = hash_monkey_items(&monkeys);
-        let this_top_two = find_top_two(&num_items_inspected);
-        if let Some(last_seen_round) = seen_states.insert(hash, round) {
-
-            let rounds_per_cycle = round - last_seen_round;
-            let remaining_rounds = total_rounds - round;
-            let remaining_full_cycles = remaining_rounds / rounds_per_cycle; // Rounded down
-            let remainder_rounds_in_partial_cycle = remaining_rounds % rounds_per_cycle; // Remainder
-
-            // Calculate expected inspections by top-two monkeys after skipping the remaining full cycles
-            let (last_t1, last_t2) = top_two_per_round[last_seen_round];
-            let (this_t1, this_t2) = this_top_two;
-            let t1 = this_t1 + (this_t1 - last_t1) * remaining_full_cycles;
-            let t2 = this_t2 + (this_t2 - last_t2) * remaining_full_cycles;
-
-            // Calculate expected inspections by top-two monkeys after skipping the remaining rounds of a partial cycle
-            let final_round_in_last_cycle = last_seen_round + remainder_rounds_in_partial_cycle;
-            let (last_part_t1, last_part_t2) = top_two_per_round[final_round_in_last_cycle - 1];
-            let t1 = t1 + (last_part_t1 - last_t1);
-            let t2 = t2 + (last_part_t2 - last_t2);
-
-            return t1 * t2;
+        if cycle_detector.record(hash, num_items_inspected.clone()).is_some() {
+            // Skip the remaining rounds by extrapolating inspections from the cycle
+            num_items_inspected = cycle_detector.extrapolate(total_rounds - 1).unwrap();
+            break;
         }
-        top_two_per_round.push(this_top_two);
-
-        // println!("\nRound {}", round + 1);
-        // monkeys.iter().enumerate().for_each(|(i, m)| {
-        //     println!("{i}: {:?}", (i, &m.items))
-        // });
-
-        round += 1
     }
 
     let (t1, t2) = find_top_two(&num_items_inspected);
@@ -1,6 +1,5 @@
 use grid::*;
 use itertools::Itertools;
-use std::collections::VecDeque;
 
 #[derive(Eq, PartialEq)]
 pub enum MapSquare {
@@ -28,7 +27,7 @@ impl MapSquare {
     }
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
 pub struct Position2D {
     pub x: i32,
     pub y: i32
@@ -68,6 +67,51 @@ impl Map {
         }
         unreachable!();
     }
+
+    // Draw the path over the map using the puzzle's arrow notation.
+    // Squares off the path are either blank or shaded by their elevation.
+    pub fn render_path(&self, path: &[Position2D], shade_elevation: bool) -> String {
+        let shades = b" .:-=+*#%@";
+        let mut canvas = Grid::init(self.grid.rows(), self.grid.cols(), '.');
+        for y in 0..self.grid.rows() {
+            for x in 0..self.grid.cols() {
+                canvas[y][x] = match self.grid[y][x] {
+                    MapSquare::Goal => 'E',
+                    _ if shade_elevation => {
+                        let height = self.grid[y][x].get_height() as usize;
+                        shades[height * shades.len() / 26] as char
+                    },
+                    _ => '.'
+                };
+            }
+        }
+
+        for step in path.windows(2) {
+            let (from, to) = (step[0], step[1]);
+            canvas[from.y as usize][from.x as usize] = match (to.x - from.x, to.y - from.y) {
+                (0, -1) => '^',
+                (0, 1) => 'v',
+                (-1, 0) => '<',
+                (1, 0) => '>',
+                _ => unreachable!()
+            };
+        }
+
+        let mut output = String::with_capacity(canvas.rows() * (canvas.cols() + 2));
+        for y in 0..canvas.rows() {
+            output.extend(canvas.iter_row(y));
+            output.push_str("\r\n");
+        }
+        output
+    }
+
+    pub fn find_all<'a>(&'a self, is_match: impl Fn(&MapSquare) -> bool + 'a) -> impl Iterator<Item=Position2D> + 'a {
+        let row_indices = 0..self.grid.rows();
+        let column_indices = 0..self.grid.cols();
+        row_indices.cartesian_product(column_indices)
+            .filter(move |(y, x)| is_match(self.grid.get(*y, *x).unwrap()))
+            .map(|(y, x)| Position2D {x: x as i32, y: y as i32})
+    }
 }
 
 pub fn find_traversable_neighbors<'a>(map: &'a Map, position: Position2D, can_move_to: &impl Fn(&MapSquare, &MapSquare) -> bool) -> Vec<(Position2D, &'a MapSquare)> {
@@ -93,44 +137,67 @@ pub fn find_traversable_neighbors<'a>(map: &'a Map, position: Position2D, can_mo
     }).collect_vec();
 }
 
-// Dijkstra's algorithm
-pub fn dijkstra_find_shortest_path_length(map: &Map, start: Position2D, goal: &MapSquare, can_move_to: &impl Fn(&MapSquare, &MapSquare) -> bool) -> Option<i32> {
-        let mut unvisited_squares = VecDeque::new();
-        let mut algorithm_state = Grid::init(map.grid.rows(), map.grid.cols(), (-1, None));
-    
-        unvisited_squares.push_back(start);
-        *algorithm_state.get_mut(start.y as usize, start.x as usize).unwrap() = (-1, Some(start));
-    
-        // Find shortest path
-        while !unvisited_squares.is_empty() {
-            let position = unvisited_squares.pop_front().unwrap();
-            let (distance, prev) = *algorithm_state.get(position.y as usize, position.x as usize).unwrap();
-            let prev = prev.unwrap();
-    
-            if distance != -1 {
-                // Already visited
-                continue;
-            }
-    
-            // Update distance of this square from start
-            let (prev_distance, _) = *algorithm_state.get(prev.y as usize, prev.x as usize).unwrap();
-            let (this_distance, _) = algorithm_state.get_mut(position.y as usize, position.x as usize).unwrap();
-            *this_distance = prev_distance + 1;
-    
-            // Queue all neighbors to be visited
-            let neighbors = find_traversable_neighbors(map, position, can_move_to);
-            for (neighbor_position, neighbor_square) in neighbors {
-                let (_, neighbor_previous) = algorithm_state.get_mut(neighbor_position.y as usize, neighbor_position.x as usize).unwrap();
-                *neighbor_previous = Some(position);
-                unvisited_squares.push_back(neighbor_position);
-    
-                if neighbor_square == goal {
-                    // We found the goal and the path to it
-                    return Some(prev_distance + 2); // distance of previous + this tile (1) + neighbor/goal tile (1)
-                }
-            }
+#[cfg(test)]
+mod tests {
+    use super::{Map, MapSquare, Position2D};
+    use super::super::pathfinding::bfs;
+
+    fn can_move_to(from: &MapSquare, to: &MapSquare) -> bool {
+        to.get_height() <= from.get_height() + 1
+    }
+
+    #[test]
+    fn verify_render_puzzle_path() {
+        // The route drawn in the puzzle description (one of several shortest paths)
+        let map = Map::new_from_string(super::super::INPUT_EXAMPLE);
+        let mut position = map.find_first(&MapSquare::Start);
+        let mut path = vec![position];
+        for arrow in "v>v>vv>>>>>^^^^<<<<vvv>>>^^<<v>".chars() {
+            let (dx, dy) = match arrow {
+                '^' => (0, -1),
+                'v' => (0, 1),
+                '<' => (-1, 0),
+                '>' => (1, 0),
+                _ => unreachable!()
+            };
+            let next = Position2D {x: position.x + dx, y: position.y + dy};
+            assert!(can_move_to(map.get_square_at(&position).unwrap(), map.get_square_at(&next).unwrap()));
+            path.push(next);
+            position = next;
         }
 
-    //
-    None
-}
\ No newline at end of file
+        assert_eq!(
+            map.render_path(&path, false),
+            "v..v<<<<\r\n\
+             >v.vv<<^\r\n\
+             .>vv>E^^\r\n\
+             ..v>>>^^\r\n\
+             ..>>>>>^\r\n"
+        );
+    }
+
+    #[test]
+    fn verify_render_found_path() {
+        let map = Map::new_from_string(super::super::INPUT_EXAMPLE);
+        let start = map.find_first(&MapSquare::Start);
+        let is_goal = |_, square: &MapSquare| *square == MapSquare::Goal;
+        let path = bfs(&map, &[start], is_goal, &can_move_to).unwrap();
+
+        assert_eq!(
+            map.render_path(&path.positions, false),
+            "v..v<<<<\r\n\
+             >v.vv<<^\r\n\
+             .v.v>E^^\r\n\
+             .>v>>>^^\r\n\
+             ..>>>>>^\r\n"
+        );
+        let elevation = [
+            "   *+++=",
+            "   *@%%=",
+            "   *@E%-",
+            "   ##%%-",
+            "  ...::-",
+        ];
+        assert_eq!(map.render_path(&[], true), elevation.join("\r\n") + "\r\n");
+    }
+}
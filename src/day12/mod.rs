@@ -1,6 +1,7 @@
 pub mod lib;
 pub mod part1;
 pub mod part2;
+pub mod pathfinding;
 
 pub static INPUT: &str = include_str!("./input.txt");
 pub static INPUT_EXAMPLE: &str = include_str!("./input_example.txt");
@@ -1,11 +1,12 @@
-use super::lib::{Map, MapSquare, dijkstra_find_shortest_path_length};
+use super::lib::{Map, MapSquare};
+use super::pathfinding::bfs;
 
-pub fn solve(input: &str) -> i32 {
+pub fn solve(input: &str) -> usize {
     let map = Map::new_from_string(input);
     let can_move_to = |from: &MapSquare, to: &MapSquare| to.get_height() <= from.get_height() + 1;
     let start = map.find_first(&MapSquare::Start);
-    let goal = MapSquare::Goal;
-    dijkstra_find_shortest_path_length(&map, start, &goal, &can_move_to).unwrap()
+    let is_goal = |_, square: &MapSquare| *square == MapSquare::Goal;
+    bfs(&map, &[start], is_goal, &can_move_to).unwrap().steps()
 }
 
 pub mod tests {
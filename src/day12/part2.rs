@@ -1,11 +1,14 @@
-use super::lib::{Map, MapSquare, dijkstra_find_shortest_path_length};
+use itertools::Itertools;
+use super::lib::{Map, MapSquare};
+use super::pathfinding::bfs;
 
-pub fn solve(input: &str) -> i32 {
+pub fn solve(input: &str) -> usize {
     let map = Map::new_from_string(input);
-    let can_move_to = |to: &MapSquare, from: &MapSquare| to.get_height() <= from.get_height() + 1;
-    let start = map.find_first(&MapSquare::Goal);
-    let goal = MapSquare::Terrain(0);
-    dijkstra_find_shortest_path_length(&map, start, &goal, &can_move_to).unwrap()
+    let can_move_to = |from: &MapSquare, to: &MapSquare| to.get_height() <= from.get_height() + 1;
+    // Search from every square at level `a` at once (including the marked start)
+    let starts = map.find_all(|square| square.get_height() == 0).collect_vec();
+    let is_goal = |_, square: &MapSquare| *square == MapSquare::Goal;
+    bfs(&map, &starts, is_goal, &can_move_to).unwrap().steps()
 }
 
 pub mod tests {
@@ -0,0 +1,183 @@
+use grid::Grid;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+use super::lib::{Map, MapSquare, Position2D, find_traversable_neighbors};
+
+pub struct Path {
+    pub cost: u32,
+    pub positions: Vec<Position2D>, // Includes both the start and goal positions
+}
+
+impl Path {
+    pub fn steps(&self) -> usize {
+        self.positions.len() - 1
+    }
+}
+
+fn reconstruct_path(prev: &Grid<Option<Position2D>>, goal: Position2D, cost: u32) -> Path {
+    let mut positions = vec![goal];
+    let mut position = goal;
+    while let Some(prev_position) = prev[position.y as usize][position.x as usize] {
+        positions.push(prev_position);
+        position = prev_position;
+    }
+    positions.reverse();
+    Path { cost, positions }
+}
+
+// Breadth-first search, treating every move as a cost of 1.
+// Searching from multiple sources at once finds the shortest path from whichever is closest.
+pub fn bfs(
+    map: &Map,
+    sources: &[Position2D],
+    is_goal: impl Fn(Position2D, &MapSquare) -> bool,
+    can_move_to: &impl Fn(&MapSquare, &MapSquare) -> bool
+) -> Option<Path> {
+    let mut visited = Grid::init(map.grid.rows(), map.grid.cols(), false);
+    let mut prev = Grid::init(map.grid.rows(), map.grid.cols(), None);
+    let mut queue = VecDeque::new();
+
+    for &source in sources {
+        visited[source.y as usize][source.x as usize] = true;
+        queue.push_back((source, 0));
+    }
+
+    while let Some((position, distance)) = queue.pop_front() {
+        if is_goal(position, map.get_square_at(&position).unwrap()) {
+            return Some(reconstruct_path(&prev, position, distance));
+        }
+
+        for (neighbor, _) in find_traversable_neighbors(map, position, can_move_to) {
+            let seen = &mut visited[neighbor.y as usize][neighbor.x as usize];
+            if !*seen {
+                *seen = true;
+                prev[neighbor.y as usize][neighbor.x as usize] = Some(position);
+                queue.push_back((neighbor, distance + 1));
+            }
+        }
+    }
+
+    None
+}
+
+// Best-first search with weighted moves. A heuristic of zero gives Dijkstra's algorithm.
+fn best_first_search(
+    map: &Map,
+    sources: &[Position2D],
+    is_goal: impl Fn(Position2D, &MapSquare) -> bool,
+    can_move_to: &impl Fn(&MapSquare, &MapSquare) -> bool,
+    cost: impl Fn(&MapSquare, &MapSquare) -> u32,
+    heuristic: impl Fn(Position2D) -> u32
+) -> Option<Path> {
+    let mut distances = Grid::init(map.grid.rows(), map.grid.cols(), u32::MAX);
+    let mut prev = Grid::init(map.grid.rows(), map.grid.cols(), None);
+    let mut queue = BinaryHeap::new();
+
+    for &source in sources {
+        distances[source.y as usize][source.x as usize] = 0;
+        queue.push(Reverse((heuristic(source), 0, source.x, source.y)));
+    }
+
+    while let Some(Reverse((_, distance, x, y))) = queue.pop() {
+        let position = Position2D { x, y };
+        if distance > distances[y as usize][x as usize] {
+            // Already visited with a shorter distance
+            continue;
+        }
+
+        let square = map.get_square_at(&position).unwrap();
+        if is_goal(position, square) {
+            return Some(reconstruct_path(&prev, position, distance));
+        }
+
+        for (neighbor, neighbor_square) in find_traversable_neighbors(map, position, can_move_to) {
+            let neighbor_distance = distance + cost(square, neighbor_square);
+            let best = &mut distances[neighbor.y as usize][neighbor.x as usize];
+            if neighbor_distance < *best {
+                *best = neighbor_distance;
+                prev[neighbor.y as usize][neighbor.x as usize] = Some(position);
+                let estimate = neighbor_distance + heuristic(neighbor);
+                queue.push(Reverse((estimate, neighbor_distance, neighbor.x, neighbor.y)));
+            }
+        }
+    }
+
+    None
+}
+
+pub fn dijkstra(
+    map: &Map,
+    sources: &[Position2D],
+    is_goal: impl Fn(Position2D, &MapSquare) -> bool,
+    can_move_to: &impl Fn(&MapSquare, &MapSquare) -> bool,
+    cost: impl Fn(&MapSquare, &MapSquare) -> u32
+) -> Option<Path> {
+    best_first_search(map, sources, is_goal, can_move_to, cost, |_| 0)
+}
+
+// A* towards a single goal using the Manhattan distance as the heuristic.
+// This is only guaranteed to find the shortest path if every move costs at least 1.
+pub fn a_star(
+    map: &Map,
+    sources: &[Position2D],
+    goal: Position2D,
+    can_move_to: &impl Fn(&MapSquare, &MapSquare) -> bool,
+    cost: impl Fn(&MapSquare, &MapSquare) -> u32
+) -> Option<Path> {
+    let manhattan_distance = |position: Position2D| position.x.abs_diff(goal.x) + position.y.abs_diff(goal.y);
+    best_first_search(map, sources, |position, _| position == goal, can_move_to, cost, manhattan_distance)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::lib::{Map, MapSquare};
+
+    fn can_climb(from: &MapSquare, to: &MapSquare) -> bool {
+        to.get_height() <= from.get_height() + 1
+    }
+
+    #[test]
+    fn verify_algorithms_agree() {
+        let map = Map::new_from_string(super::super::INPUT);
+        let start = map.find_first(&MapSquare::Start);
+        let goal = map.find_first(&MapSquare::Goal);
+        let is_goal = |_, square: &MapSquare| *square == MapSquare::Goal;
+
+        let bfs = super::bfs(&map, &[start], is_goal, &can_climb).unwrap();
+        let dijkstra = super::dijkstra(&map, &[start], is_goal, &can_climb, |_, _| 1).unwrap();
+        let a_star = super::a_star(&map, &[start], goal, &can_climb, |_, _| 1).unwrap();
+        assert_eq!(bfs.steps(), 528);
+        assert_eq!(dijkstra.cost, 528);
+        assert_eq!(a_star.cost, 528);
+        assert_eq!(a_star.positions.len(), bfs.positions.len());
+    }
+
+    #[test]
+    fn verify_path_is_traversable() {
+        let map = Map::new_from_string(super::super::INPUT_EXAMPLE);
+        let start = map.find_first(&MapSquare::Start);
+        let goal = map.find_first(&MapSquare::Goal);
+        let path = super::a_star(&map, &[start], goal, &can_climb, |_, _| 1).unwrap();
+
+        assert_eq!(path.positions.first(), Some(&start));
+        assert_eq!(path.positions.last(), Some(&goal));
+        for step in path.positions.windows(2) {
+            let (from, to) = (step[0], step[1]);
+            assert_eq!(from.x.abs_diff(to.x) + from.y.abs_diff(to.y), 1);
+            assert!(can_climb(map.get_square_at(&from).unwrap(), map.get_square_at(&to).unwrap()));
+        }
+    }
+
+    #[test]
+    fn verify_weighted_costs() {
+        // Climbing costs more than walking on the flat, so the cheapest path is at least as long
+        let map = Map::new_from_string(super::super::INPUT_EXAMPLE);
+        let start = map.find_first(&MapSquare::Start);
+        let is_goal = |_, square: &MapSquare| *square == MapSquare::Goal;
+        let cost = |from: &MapSquare, to: &MapSquare| if to.get_height() > from.get_height() { 5 } else { 1 };
+
+        let path = super::dijkstra(&map, &[start], is_goal, &can_climb, cost).unwrap();
+        assert_eq!(path.cost, 31 + 4 * 25);
+        assert!(path.steps() >= 31);
+    }
+}
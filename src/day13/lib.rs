@@ -130,4 +130,167 @@ pub fn compare_data(left: &str, right: &str) -> ComparisonResult {
     }
 
     ComparisonResult::Indeterminate
-}
\ No newline at end of file
+}
+
+enum Token {
+    Open,
+    Close,
+    Separator,
+    Integer(u32)
+}
+
+// Reads tokens from a packet string, allowing integers to be virtually wrapped in lists
+struct TokenStream<'a> {
+    data: &'a [u8],
+    position: usize,
+    wraps_before_integer: usize, // Virtual lists opened around the upcoming integer
+    virtual_closes: usize        // Virtual lists still to be closed after that integer
+}
+
+impl<'a> TokenStream<'a> {
+    fn new(data: &'a str) -> TokenStream<'a> {
+        TokenStream { data: data.as_bytes(), position: 0, wraps_before_integer: 0, virtual_closes: 0 }
+    }
+
+    fn is_finished(&self) -> bool {
+        self.position >= self.data.len() && self.virtual_closes == 0
+    }
+
+    fn peek(&self) -> Token {
+        if self.virtual_closes > 0 {
+            return Token::Close;
+        }
+
+        match self.data[self.position] {
+            b'[' => Token::Open,
+            b']' => Token::Close,
+            b',' => Token::Separator,
+            _ => {
+                let mut value = 0;
+                for digit in self.data[self.position..].iter().take_while(|c| c.is_ascii_digit()) {
+                    value = (value * 10) + (digit - b'0') as u32;
+                }
+                Token::Integer(value)
+            }
+        }
+    }
+
+    fn advance(&mut self) {
+        if self.virtual_closes > 0 {
+            self.virtual_closes -= 1;
+            return;
+        }
+
+        if self.data[self.position].is_ascii_digit() {
+            while self.position < self.data.len() && self.data[self.position].is_ascii_digit() {
+                self.position += 1;
+            }
+            // Close any lists we pretended this integer was wrapped in
+            self.virtual_closes = self.wraps_before_integer;
+            self.wraps_before_integer = 0;
+        } else {
+            self.position += 1;
+        }
+    }
+
+    // Treat the upcoming integer as if it were inside one more list
+    fn wrap_integer(&mut self) {
+        self.wraps_before_integer += 1;
+    }
+}
+
+// Compare two packets in a single left-to-right pass without allocating
+pub fn compare_data_streaming(left: &str, right: &str) -> ComparisonResult {
+    let mut left = TokenStream::new(left);
+    let mut right = TokenStream::new(right);
+
+    while !left.is_finished() && !right.is_finished() {
+        match (left.peek(), right.peek()) {
+            (Token::Integer(left_int), Token::Integer(right_int)) => {
+                if left_int < right_int {
+                    return ComparisonResult::CorrectOrder;
+                } else if left_int > right_int {
+                    return ComparisonResult::IncorrectOrder;
+                }
+                left.advance();
+                right.advance();
+            },
+            // "If the left list runs out of items first, the inputs are in the right order."
+            (Token::Close, Token::Close) => {
+                left.advance();
+                right.advance();
+            },
+            (Token::Close, _) => return ComparisonResult::CorrectOrder,
+            // "If the right list runs out of items first, the inputs are not in the right order."
+            (_, Token::Close) => return ComparisonResult::IncorrectOrder,
+            // Compare an int and a list
+            (Token::Integer(_), Token::Open) => {
+                left.wrap_integer();
+                right.advance();
+            },
+            (Token::Open, Token::Integer(_)) => {
+                left.advance();
+                right.wrap_integer();
+            },
+            // Both sides are at the same point in the structure
+            _ => {
+                left.advance();
+                right.advance();
+            }
+        }
+    }
+
+    ComparisonResult::Indeterminate
+}
+
+#[cfg(test)]
+mod tests {
+    use oorandom::Rand32;
+    use super::{ComparisonResult, compare_data, compare_data_streaming};
+
+    fn random_packet(rng: &mut Rand32, depth: u32, output: &mut String) {
+        output.push('[');
+        // Keep lists short and values few so that pairs often match deep into the structure
+        let length = if depth >= 4 { 0 } else { rng.rand_range(0..4) };
+        for i in 0..length {
+            if i > 0 {
+                output.push(',');
+            }
+            if rng.rand_range(0..3) == 0 {
+                random_packet(rng, depth + 1, output);
+            } else {
+                let value = [0, 1, 2, 10][rng.rand_range(0..4) as usize];
+                output.push_str(&value.to_string());
+            }
+        }
+        output.push(']');
+    }
+
+    #[test]
+    fn verify_check_pairs() {
+        assert_eq!(compare_data_streaming("[1,1,3,1,1]", "[1,1,5,1,1]"), ComparisonResult::CorrectOrder);
+        assert_eq!(compare_data_streaming("[[1],[2,3,4]]", "[[1],4]"), ComparisonResult::CorrectOrder);
+        assert_eq!(compare_data_streaming("[9]", "[[8,7,6]]"), ComparisonResult::IncorrectOrder);
+        assert_eq!(compare_data_streaming("[[4,4],4,4]", "[[4,4],4,4,4]"), ComparisonResult::CorrectOrder);
+        assert_eq!(compare_data_streaming("[7,7,7,7]", "[7,7,7]"), ComparisonResult::IncorrectOrder);
+        assert_eq!(compare_data_streaming("[]", "[3]"), ComparisonResult::CorrectOrder);
+        assert_eq!(compare_data_streaming("[[[]]]", "[[]]"), ComparisonResult::IncorrectOrder);
+        assert_eq!(compare_data_streaming("[1,[2,[3,[4,[5,6,7]]]],8,9]", "[1,[2,[3,[4,[5,6,0]]]],8,9]"), ComparisonResult::IncorrectOrder);
+        assert_eq!(compare_data_streaming("[10]", "[[[10]]]"), ComparisonResult::Indeterminate);
+        assert_eq!(compare_data_streaming("[[[10]],1]", "[10,0]"), ComparisonResult::IncorrectOrder);
+    }
+
+    #[test]
+    fn verify_differential_fuzz() {
+        let mut rng = Rand32::new(2022);
+        for _ in 0..20_000 {
+            let mut left = String::new();
+            let mut right = String::new();
+            random_packet(&mut rng, 0, &mut left);
+            random_packet(&mut rng, 0, &mut right);
+
+            assert_eq!(compare_data_streaming(&left, &right), compare_data(&left, &right), "{} vs {}", left, right);
+            assert_eq!(compare_data_streaming(&left, &left), ComparisonResult::Indeterminate, "{}", left);
+        }
+    }
+}
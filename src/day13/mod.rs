@@ -1,7 +1,9 @@
 pub mod lib;
+pub mod packet;
 pub mod part1;
 pub mod part2_solve1;
 pub mod part2_solve2;
+pub mod part2_solve3;
 
 pub static INPUT: &str = include_str!("./input.txt");
 pub static INPUT_EXAMPLE: &str = include_str!("./input_example.txt");
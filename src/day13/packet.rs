@@ -0,0 +1,185 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Clone, Debug)]
+pub enum Packet {
+    Int(u32),
+    List(Vec<Packet>)
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParsePacketError {
+    pub position: usize // Byte offset of the unexpected character (or end of input)
+}
+
+// Recursive-descent parser over the bytes of a single packet
+struct Parser<'a> {
+    bytes: &'a [u8],
+    position: usize
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.position).copied()
+    }
+
+    fn error(&self) -> ParsePacketError {
+        ParsePacketError { position: self.position }
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), ParsePacketError> {
+        if self.peek() == Some(byte) {
+            self.position += 1;
+            Ok(())
+        } else {
+            Err(self.error())
+        }
+    }
+
+    fn parse_packet(&mut self) -> Result<Packet, ParsePacketError> {
+        match self.peek() {
+            Some(b'[') => self.parse_list(),
+            Some(b'0'..=b'9') => self.parse_int(),
+            _ => Err(self.error())
+        }
+    }
+
+    fn parse_list(&mut self) -> Result<Packet, ParsePacketError> {
+        self.expect(b'[')?;
+        let mut items = Vec::new();
+        if self.peek() == Some(b']') {
+            self.position += 1;
+            return Ok(Packet::List(items));
+        }
+
+        loop {
+            items.push(self.parse_packet()?);
+            match self.peek() {
+                Some(b',') => self.position += 1,
+                Some(b']') => {
+                    self.position += 1;
+                    return Ok(Packet::List(items));
+                },
+                _ => return Err(self.error())
+            }
+        }
+    }
+
+    fn parse_int(&mut self) -> Result<Packet, ParsePacketError> {
+        let start = self.position;
+        while let Some(b'0'..=b'9') = self.peek() {
+            self.position += 1;
+        }
+        let digits = std::str::from_utf8(&self.bytes[start..self.position]).unwrap();
+        digits.parse().map(Packet::Int).map_err(|_| ParsePacketError { position: start })
+    }
+}
+
+impl FromStr for Packet {
+    type Err = ParsePacketError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser { bytes: s.as_bytes(), position: 0 };
+        let packet = parser.parse_list()?;
+        if parser.position != s.len() {
+            // Trailing characters after the outermost list
+            return Err(parser.error());
+        }
+        Ok(packet)
+    }
+}
+
+impl fmt::Display for Packet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Packet::Int(value) => write!(f, "{}", value),
+            Packet::List(items) => {
+                write!(f, "[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, "]")
+            }
+        }
+    }
+}
+
+impl Ord for Packet {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Packet::Int(left), Packet::Int(right)) => left.cmp(right),
+            // Lists compare item by item, and then by length if one runs out first
+            (Packet::List(left), Packet::List(right)) => left.cmp(right),
+            // An integer compared to a list is treated as a list containing only that integer
+            (Packet::Int(_), Packet::List(right)) => std::slice::from_ref(self).cmp(right.as_slice()),
+            (Packet::List(left), Packet::Int(_)) => left.as_slice().cmp(std::slice::from_ref(other)),
+        }
+    }
+}
+
+impl PartialOrd for Packet {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// Equality follows the puzzle's ordering rather than the structure, so `[[2]]` and `[2]` are equal.
+impl PartialEq for Packet {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Packet {}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+    use itertools::Itertools;
+    use super::{Packet, ParsePacketError};
+
+    #[test]
+    fn verify_round_trip() {
+        for line in super::super::INPUT.lines().filter(|line| !line.is_empty()) {
+            let packet: Packet = line.parse().unwrap();
+            assert_eq!(packet.to_string(), line);
+        }
+    }
+
+    #[test]
+    fn verify_parse_errors() {
+        assert_eq!("".parse::<Packet>(), Err(ParsePacketError { position: 0 }));
+        assert_eq!("[1,2".parse::<Packet>(), Err(ParsePacketError { position: 4 }));
+        assert_eq!("[1,,2]".parse::<Packet>(), Err(ParsePacketError { position: 3 }));
+        assert_eq!("[1]]".parse::<Packet>(), Err(ParsePacketError { position: 3 }));
+        assert_eq!("7".parse::<Packet>(), Err(ParsePacketError { position: 0 }));
+    }
+
+    #[test]
+    fn verify_ordering() {
+        let pairs = super::super::INPUT_EXAMPLE
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(|line| line.parse::<Packet>().unwrap())
+            .tuples::<(_, _)>()
+            .map(|(left, right)| left < right)
+            .collect_vec();
+        assert_eq!(pairs, vec![true, true, false, true, false, true, false, false]);
+    }
+
+    #[test]
+    fn verify_btree_set() {
+        let packets: BTreeSet<Packet> = ["[[6]]", "[1,1]", "[2]", "[]"]
+            .iter()
+            .map(|packet| packet.parse().unwrap())
+            .collect();
+        let sorted = packets.iter().map(|packet| packet.to_string()).collect_vec();
+        assert_eq!(sorted, vec!["[]", "[1,1]", "[2]", "[[6]]"]);
+        // Packets that only differ by wrapping an integer in a list are equivalent
+        assert!(packets.contains(&"[[2]]".parse().unwrap()));
+    }
+}
@@ -1,40 +1,25 @@
-use std::cmp::Ordering;
-
 use itertools::Itertools;
-
-use super::lib::{ComparisonResult, compare_data};
-
-fn find_decoder_key(packets: &Vec<&str>) -> usize {
-    let mut decoder_key = 1;
-    for (i, packet) in packets.iter().enumerate() {
-        if packet == &"[[2]]" || packet == &"[[6]]" {
-            decoder_key *= i + 1;
-            if decoder_key != i + 1 {
-                // We found the second packet
-                return decoder_key;
-            }
-        }
-    }
-    unreachable!()
-}
+use super::packet::Packet;
 
 pub fn solve(input: &str) -> usize {
+    let dividers: [Packet; 2] = ["[[2]]".parse().unwrap(), "[[6]]".parse().unwrap()];
     let mut packets = input
-    .split("\r\n")
-    .filter(|x| !x.is_empty())
-    .chain(["[[2]]", "[[6]]"])
-    .collect_vec();
-
-    packets.sort_unstable_by(|left, right| {
-        match compare_data(left, right) {
-            ComparisonResult::CorrectOrder => Ordering::Less,
-            ComparisonResult::IncorrectOrder => Ordering::Greater,
-            ComparisonResult::Indeterminate => Ordering::Equal,
-        }
-    });
-    //packets.iter().for_each(|x| println!("{}",x));
-
-    find_decoder_key(&packets)
+        .lines()
+        .filter(|x| !x.is_empty())
+        .map(|x| x.parse::<Packet>().unwrap())
+        .collect_vec();
+    let input_count = packets.len();
+    packets.extend(dividers);
+
+    // Tag each packet with where it came from, since different packets such as [2] and [[2]] can compare as equal.
+    // Ties are broken by the tag, so the order is the same every time.
+    let mut tagged = packets.iter().enumerate().collect_vec();
+    tagged.sort_unstable_by(|(a_i, a), (b_i, b)| a.cmp(b).then(a_i.cmp(b_i)));
+
+    tagged.iter()
+        .positions(|&(i, _)| i >= input_count)
+        .map(|position| position + 1)
+        .product()
 }
 
 pub mod tests {
@@ -46,6 +31,12 @@ pub mod tests {
         assert_eq!(super::solve(super::super::INPUT_EXAMPLE), 140);
     }
 
+    #[test]
+    fn verify_equal_to_divider() {
+        // [2] and [[[2]]] compare as equal to the [[2]] divider, so must not be mistaken for it
+        assert_eq!(super::solve("[2]\n[[[2]]]\n[3]\n"), 3 * 5);
+    }
+
     #[test]
     fn verify_solution() {
         assert_eq!(super::solve(super::super::INPUT), 20952);
@@ -3,25 +3,22 @@ use super::lib::{ComparisonResult, compare_data};
 
 pub fn solve(input: &str) -> usize {
     let packets = input
-    .split("\r\n")
+    .lines()
     .filter(|x| !x.is_empty())
     .collect_vec();
 
-    // Find index of each divider packet
+    // Find index of each divider packet. Packets that compare as equal to a divider, such as [2] and [[2]],
+    // are sorted before it.
     let two_index = packets.iter().filter(|packet| {
-        compare_data(packet, "[[2]]") == ComparisonResult::CorrectOrder
+        compare_data(packet, "[[2]]") != ComparisonResult::IncorrectOrder
     }).count() + 1;
 
     let six_index = packets.iter().filter(|packet| {
-        compare_data(packet, "[[6]]") == ComparisonResult::CorrectOrder
+        compare_data(packet, "[[6]]") != ComparisonResult::IncorrectOrder
     }).count() + 1;
 
-    // Account for the two divider packets not actually being in the input
-    if two_index < six_index {
-        two_index * (six_index + 1)
-    } else {
-        (two_index + 1) * six_index
-    }
+    // Account for the [[2]] divider packet not actually being in the input, as it always sorts before [[6]]
+    two_index * (six_index + 1)
 }
 
 pub mod tests {
@@ -33,6 +30,12 @@ pub mod tests {
         assert_eq!(super::solve(super::super::INPUT_EXAMPLE), 140);
     }
 
+    #[test]
+    fn verify_equal_to_divider() {
+        // [2] and [[[2]]] compare as equal to the [[2]] divider, so must not be mistaken for it
+        assert_eq!(super::solve("[2]\n[[[2]]]\n[3]\n"), 3 * 5);
+    }
+
     #[test]
     fn verify_solution() {
         assert_eq!(super::solve(super::super::INPUT), 20952);
@@ -0,0 +1,49 @@
+use super::lib::{ComparisonResult, compare_data_streaming};
+
+pub fn solve(input: &str) -> usize {
+    let packets = || input
+    .lines()
+    .filter(|x| !x.is_empty());
+
+    // Find index of each divider packet without allocating. Packets that compare as equal to a divider,
+    // such as [2] and [[2]], are sorted before it.
+    let two_index = packets().filter(|packet| {
+        compare_data_streaming(packet, "[[2]]") != ComparisonResult::IncorrectOrder
+    }).count() + 1;
+
+    let six_index = packets().filter(|packet| {
+        compare_data_streaming(packet, "[[6]]") != ComparisonResult::IncorrectOrder
+    }).count() + 1;
+
+    // Account for the [[2]] divider packet not actually being in the input, as it always sorts before [[6]]
+    two_index * (six_index + 1)
+}
+
+pub mod tests {
+    use criterion::Criterion;
+    use std::path::Path;
+
+    #[test]
+    fn verify_example() {
+        assert_eq!(super::solve(super::super::INPUT_EXAMPLE), 140);
+    }
+
+    #[test]
+    fn verify_equal_to_divider() {
+        // [2] and [[[2]]] compare as equal to the [[2]] divider, so must not be mistaken for it
+        assert_eq!(super::solve("[2]\n[[[2]]]\n[3]\n"), 3 * 5);
+    }
+
+    #[test]
+    fn verify_solution() {
+        assert_eq!(super::solve(super::super::INPUT), 20952);
+    }
+
+    pub fn benchmark(c: &mut Criterion) {
+        let path = Path::new(file!());
+        let day = path.parent().unwrap().to_str().unwrap();
+        let solution = path.file_stem().unwrap().to_str().unwrap();
+        let id = day.to_owned() + "_" + solution; // dayX_partY_solveZ
+        c.bench_function( &id, |b| b.iter(|| super::solve(super::super::INPUT)));
+    }
+}
\ No newline at end of file
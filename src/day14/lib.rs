@@ -1,5 +1,6 @@
 use grid::Grid;
 use itertools::Itertools;
+use std::ops::RangeInclusive;
 
 #[derive(Clone, Eq, PartialEq, Hash, Debug, Copy)]
 pub struct Position2D {
@@ -8,62 +9,438 @@ pub struct Position2D {
 }
 
 impl Position2D {
+    #[inline(always)]
     pub fn below(&self) -> Position2D {
         Position2D {x: self.x, y: self.y + 1}
     }
 
+    #[inline(always)]
     pub fn left(&self) -> Position2D {
         Position2D {x: self.x - 1, y: self.y}
     }
 
+    #[inline(always)]
     pub fn right(&self) -> Position2D {
         Position2D {x: self.x + 1, y: self.y}
     }
+
+    // Moving past zero wraps around to a position far outside any cave
+    #[inline(always)]
+    pub fn offset(&self, dx: isize, dy: isize) -> Position2D {
+        Position2D {x: self.x.wrapping_add_signed(dx), y: self.y.wrapping_add_signed(dy)}
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum Width {
+    Bounded(RangeInclusive<usize>), // Solid walls on either side of this range
+    Unbounded,                      // Particles that flow past the sides are lost
+}
+
+#[derive(Clone, Debug)]
+pub struct CaveConfig {
+    pub sources: Vec<Position2D>,
+    pub floor_offset: Option<usize>, // Distance of the floor below the lowest rock, if there is one
+    pub width: Width,
+}
+
+impl Default for CaveConfig {
+    fn default() -> Self {
+        CaveConfig { sources: vec![Position2D { x: 500, y: 0 }], floor_offset: None, width: Width::Unbounded }
+    }
+}
+
+impl CaveConfig {
+    pub fn with_floor(floor_offset: usize) -> CaveConfig {
+        CaveConfig { floor_offset: Some(floor_offset), ..Default::default() }
+    }
+}
+
+// The moves of the puzzle's sand, which get a fast path since they're by far the most common
+const SAND_MOVES: [(isize, isize); 3] = [(0, 1), (-1, 1), (1, 1)];
+
+#[derive(Clone, Debug)]
+pub struct MovementRules {
+    // Moves a particle tries in order each step. It settles once none of them are possible.
+    // A particle never moves straight back to the square it just left, so it can't bounce forever.
+    moves: Vec<(isize, isize)>,
+    has_sideways: bool, // Without sideways moves a particle can't go back anyway, so we skip that check
+    is_sand: bool,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum InvalidRulesError {
+    MovesUp,          // A particle could climb back up to where it has already been
+    StandsStill,      // A (0, 0) move never gets anywhere
+    UnevenSideways,   // Sideways moves of different sizes could walk in a loop, e.g. left 1, right 2, left 1
+}
+
+impl MovementRules {
+    // Particles can only ever move down or sideways by a single distance, so they can never revisit a square
+    pub fn new(moves: Vec<(isize, isize)>) -> Result<MovementRules, InvalidRulesError> {
+        if moves.iter().any(|&(_, dy)| dy < 0) {
+            return Err(InvalidRulesError::MovesUp);
+        }
+        if moves.contains(&(0, 0)) {
+            return Err(InvalidRulesError::StandsStill);
+        }
+        if !moves.iter().filter(|(_, dy)| *dy == 0).map(|(dx, _)| dx.unsigned_abs()).all_equal() {
+            return Err(InvalidRulesError::UnevenSideways);
+        }
+        Ok(MovementRules::from_moves(moves))
+    }
+
+    fn from_moves(moves: Vec<(isize, isize)>) -> MovementRules {
+        let has_sideways = moves.iter().any(|&(_, dy)| dy == 0);
+        let is_sand = moves == SAND_MOVES;
+        MovementRules { moves, has_sideways, is_sand }
+    }
+
+    // Whether a particle could move straight back to the square it just left, if we didn't stop it
+    #[inline(always)]
+    pub fn can_turn_back(&self) -> bool {
+        self.has_sideways
+    }
+
+    pub fn sand() -> MovementRules {
+        MovementRules::from_moves(SAND_MOVES.to_vec())
+    }
+
+    // Falls straight down, otherwise spreads sideways until it hits something
+    pub fn water() -> MovementRules {
+        MovementRules::from_moves(vec![(0, 1), (-1, 0), (1, 0)])
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Cell {
+    Air,
+    Source,
+    Rock,
+    Sand,
+}
+
+impl Cell {
+    #[inline(always)]
+    pub fn is_blocking(self) -> bool {
+        matches!(self, Cell::Rock | Cell::Sand)
+    }
+
+    pub fn to_char(self) -> char {
+        match self {
+            Cell::Air => '.',
+            Cell::Rock => '#',
+            Cell::Sand => 'o',
+            Cell::Source => '+',
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Step {
+    Move(Position2D),
+    Settle,
+    Lost,  // Moved out of the cave
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DropResult {
+    Settled(Position2D),
+    Lost,          // Fell into the void or flowed out of the sides
+    SourceBlocked,
 }
 
 pub struct Cave {
-    blocked: Grid<bool>,
+    cells: Grid<Cell>, // Includes the floor, if there is one, as a row of rock at the bottom
+    lowest_level: usize,
     offset_x: usize,
     offset_y: usize,
+    floor: Option<usize>,
+    has_walls: bool,
+    sand_fits: bool, // Whether sand from any source stays inside the grid until it falls out of the bottom
+    sources: Vec<Position2D>,
+    flowing: Vec<Position2D>, // Path of the particle that was lost, if any
 }
 
 impl Cave {
 
-    pub fn new(section_min: Position2D, section_max: Position2D) -> Cave {
-        let blocked = Grid::init(
-             section_max.y - section_min.y + 1,
+    pub fn new(section_min: Position2D, section_max: Position2D, config: &CaveConfig) -> Cave {
+        // Particles land on the floor far more often than they leave the grid, so keeping it inside the grid
+        // saves looking outside it on every one of those steps
+        let floor_rows = if config.floor_offset.is_some() { 1 } else { 0 };
+        let mut cells = Grid::init(
+             section_max.y - section_min.y + 1 + floor_rows,
              section_max.x - section_min.x + 1,
-             false);
-        Cave { blocked, offset_x: section_min.x, offset_y: section_min.y }
+             Cell::Air);
+        if floor_rows > 0 {
+            let floor_row = cells.rows() - 1;
+            cells.iter_row_mut(floor_row).for_each(|cell| *cell = Cell::Rock);
+        }
+        let mut cave = Cave {
+            cells,
+            lowest_level: section_max.y,
+            offset_x: section_min.x,
+            offset_y: section_min.y,
+            floor: config.floor_offset.map(|_| section_max.y + 1),
+            has_walls: matches!(config.width, Width::Bounded(_)),
+            sand_fits: false,
+            sources: config.sources.clone(),
+            flowing: Vec::new(),
+        };
+        for source in &config.sources {
+            if cave.contains(source) {
+                cave.set_cell(*source, Cell::Source);
+            }
+        }
+        cave
     }
 
+    #[inline(always)]
     pub fn get_lowest_level(&self) -> usize {
-        self.offset_y + self.blocked.rows() - 1
+        self.lowest_level
+    }
+
+    #[inline(always)]
+    pub fn get_horizontal_range(&self) -> RangeInclusive<usize> {
+        self.offset_x..=(self.offset_x + self.cells.cols() - 1)
+    }
+
+    #[inline]
+    pub fn get_sources(&self) -> &[Position2D] {
+        &self.sources
+    }
+
+    #[inline]
+    pub fn get_cell(&self, position: &Position2D) -> Cell {
+        let row = position.y.wrapping_sub(self.offset_y);
+        let column = position.x.wrapping_sub(self.offset_x);
+        if let Some(cell) = self.cells.get(row, column) {
+            return *cell;
+        }
+        if self.floor.is_some_and(|floor| position.y >= floor) {
+            return Cell::Rock;
+        }
+        Cell::Air
+    }
+
+    #[inline(always)]
+    fn set_cell(&mut self, position: Position2D, cell: Cell) {
+        *self.cells.get_mut(
+            position.y - self.offset_y,
+            position.x - self.offset_x).unwrap() = cell;
     }
 
-    pub fn print(&self) {
-        for y in 0..self.blocked.rows() {
-            for x in 0..self.blocked.cols() {
-                if self.is_blocked(&Position2D { x,y }) {
-                    print!("X");
+    // Draw a section of the cave using the puzzle's notation, including the path of any sand
+    // that flowed into the abyss as `~`
+    pub fn render_section(&self, min: Position2D, max: Position2D) -> String {
+        let mut output = String::with_capacity((max.y - min.y + 1) * (max.x - min.x + 3));
+        for y in min.y..=max.y {
+            for x in min.x..=max.x {
+                let position = Position2D { x, y };
+                let cell = self.get_cell(&position);
+                if cell == Cell::Air && self.flowing.contains(&position) {
+                    output.push('~');
                 } else {
-                    print!(".")
+                    output.push(cell.to_char());
                 }
             }
-            println!();
+            output.push_str("\r\n");
         }
+        output
+    }
+
+    pub fn render(&self) -> String {
+        let range = self.get_horizontal_range();
+        let min = Position2D { x: *range.start(), y: self.offset_y };
+        let max = Position2D { x: *range.end(), y: self.floor.unwrap_or(self.get_lowest_level()) };
+        self.render_section(min, max)
+    }
+
+    #[inline(always)]
+    pub fn contains(&self, position: &Position2D) -> bool {
+        position.y >= self.offset_y && position.y <= self.get_lowest_level()
+            && self.get_horizontal_range().contains(&position.x)
     }
 
+    // Checked on every step of every particle, so the common case of being inside the grid comes first
+    #[inline(always)]
     pub fn is_blocked(&self, position: &Position2D) -> bool {
-        return *self.blocked.get(
-            position.y - self.offset_y,
-            position.x - self.offset_x).unwrap()
+        let row = position.y.wrapping_sub(self.offset_y);
+        let column = position.x.wrapping_sub(self.offset_x);
+        if let Some(cell) = self.cells.get(row, column) {
+            return cell.is_blocking();
+        }
+
+        // Outside the simulated area
+        if self.floor.is_some_and(|floor| position.y >= floor) {
+            return true;
+        }
+        if !self.get_horizontal_range().contains(&position.x) {
+            return self.has_walls;
+        }
+        // Falling into the void
+        false
     }
 
-    pub fn mark_blocked(&mut self, position: Position2D) {
-        *self.blocked.get_mut(
-            position.y - self.offset_y,
-            position.x - self.offset_x).unwrap() = true;
+    #[inline]
+    pub fn mark_rock(&mut self, position: Position2D) {
+        self.set_cell(position, Cell::Rock);
+    }
+
+    #[inline(always)]
+    pub fn mark_sand(&mut self, position: Position2D) {
+        self.set_cell(position, Cell::Sand);
+    }
+
+    // Find where a particle moves next, or None if it settles where it is
+    #[inline(always)]
+    pub fn next_position(&self, rules: &MovementRules, position: Position2D, previous: Option<Position2D>) -> Option<Position2D> {
+        if !rules.has_sideways {
+            return rules.moves.iter()
+                .map(|(dx, dy)| position.offset(*dx, *dy))
+                .find(|candidate| !self.is_blocked(candidate));
+        }
+        rules.moves.iter()
+            .map(|(dx, dy)| position.offset(*dx, *dy))
+            .find(|candidate| Some(*candidate) != previous && !self.is_blocked(candidate))
+    }
+
+    // As above, but also tells us if the particle has left the cave
+    #[inline(always)]
+    pub fn step(&self, rules: &MovementRules, position: Position2D, previous: Option<Position2D>) -> Step {
+        if rules.is_sand {
+            return self.sand_step(position);
+        }
+        match self.next_position(rules, position, previous) {
+            Some(next) if self.contains(&next) => Step::Move(next),
+            Some(_) => Step::Lost,
+            None => Step::Settle,
+        }
+    }
+
+    // The puzzle's sand only ever moves to one of the three squares below it. When the grid leaves room for it
+    // to spread as far as it can, those are looked up directly, and the only way out is through the bottom.
+    #[inline(always)]
+    fn sand_step(&self, position: Position2D) -> Step {
+        if !self.sand_fits {
+            return self.sand_step_with_checks(position);
+        }
+        let row = position.y + 1 - self.offset_y;
+        if row >= self.cells.rows() {
+            return Step::Lost;
+        }
+        let cells = &self.cells[row];
+        let column = position.x - self.offset_x;
+        let below = position.below();
+        if !cells[column].is_blocking() {
+            Step::Move(below)
+        } else if !cells[column - 1].is_blocking() {
+            Step::Move(below.left())
+        } else if !cells[column + 1].is_blocking() {
+            Step::Move(below.right())
+        } else {
+            Step::Settle
+        }
+    }
+
+    #[cold]
+    #[inline(never)]
+    fn sand_step_with_checks(&self, position: Position2D) -> Step {
+        for (dx, dy) in SAND_MOVES {
+            let next = position.offset(dx, dy);
+            if !self.is_blocked(&next) {
+                return if self.contains(&next) { Step::Move(next) } else { Step::Lost };
+            }
+        }
+        Step::Settle
+    }
+
+    #[inline(always)]
+    pub fn drop_particle(&self, rules: &MovementRules, source: Position2D) -> DropResult {
+        if self.is_blocked(&source) {
+            return DropResult::SourceBlocked;
+        }
+
+        // Checked once per particle, so the sand gets a loop that doesn't track the previous square
+        if rules.is_sand {
+            let mut particle = source;
+            loop {
+                match self.sand_step(particle) {
+                    Step::Move(next) => particle = next,
+                    Step::Settle => return DropResult::Settled(particle),
+                    Step::Lost => return DropResult::Lost,
+                }
+            }
+        }
+
+        let mut previous = None;
+        let mut particle = source;
+        loop {
+            match self.step(rules, particle, previous) {
+                Step::Move(next) => {
+                    previous = Some(particle);
+                    particle = next;
+                },
+                Step::Settle => return DropResult::Settled(particle),
+                Step::Lost => return DropResult::Lost,
+            }
+        }
+    }
+
+    // Follow the path of a particle that is lost into the void, including its first position
+    // outside the cave
+    fn trace_lost_particle(&self, rules: &MovementRules, source: Position2D) -> Vec<Position2D> {
+        let mut path = vec![source];
+        let mut previous = None;
+        let mut particle = source;
+        while let Some(next) = self.next_position(rules, particle, previous) {
+            path.push(next);
+            if !self.contains(&next) {
+                break;
+            }
+            previous = Some(particle);
+            particle = next;
+        }
+        path
+    }
+
+    // Drop particles from each source in turn until one is lost or every source is blocked.
+    // Returns the number of particles that settled.
+    pub fn fill(&mut self, rules: &MovementRules) -> usize {
+        self.fill_with_snapshots(rules, usize::MAX, |_, _| {})
+    }
+
+    // As above, but also calls `on_snapshot` with the number of settled particles and the cave
+    // after every `every` particles settle, and once more at the end
+    pub fn fill_with_snapshots(&mut self, rules: &MovementRules, every: usize, mut on_snapshot: impl FnMut(usize, &Cave)) -> usize {
+        let mut settled = 0;
+        let mut active_sources = self.sources.clone();
+        while !active_sources.is_empty() {
+            let mut i = 0;
+            while i < active_sources.len() {
+                match self.drop_particle(rules, active_sources[i]) {
+                    DropResult::Settled(position) => {
+                        self.mark_sand(position);
+                        settled += 1;
+                        i += 1;
+                        if settled % every == 0 {
+                            on_snapshot(settled, self);
+                        }
+                    },
+                    DropResult::SourceBlocked => {
+                        active_sources.remove(i);
+                    },
+                    DropResult::Lost => {
+                        // Remember where it went for drawing
+                        self.flowing = self.trace_lost_particle(rules, active_sources[i]);
+                        on_snapshot(settled, self);
+                        return settled;
+                    },
+                }
+            }
+        }
+        on_snapshot(settled, self);
+        settled
     }
 }
 
@@ -71,7 +448,12 @@ fn draw_rock_lines(from: Position2D, to: Position2D, cave: &mut Cave) {
     // Note: Lines are only ever vertical or horizontal so one of these loops only has a single iteration
     for x in from.x.min(to.x)..=from.x.max(to.x) {
         for y in from.y.min(to.y)..=from.y.max(to.y) {
-            cave.mark_blocked(Position2D {x,y})
+            let position = Position2D {x,y};
+            if cave.contains(&position) {
+                // Rocks outside the walls of a bounded cave, or beyond the reach of particles landing on a floor,
+                // don't matter
+                cave.mark_rock(position)
+            }
         }
     }
 }
@@ -110,13 +492,154 @@ fn find_rock_formation_bounds(input: &str) -> (Position2D, Position2D) {
 }
 
 pub fn parse_cave(input: &str) -> Cave {
+    parse_cave_with(input, &CaveConfig::default())
+}
+
+pub fn parse_cave_with(input: &str, config: &CaveConfig) -> Cave {
     let (mut min, mut max) = find_rock_formation_bounds(input);
     min.y = 0; // Ensure we can simulate all the way to the roof
-    max.y += 1; // Ensure we have a buffer below to allow the infinite floor in Part 2
-    max.x = 500 + max.y;
-    min.x = 500 - max.y;
+    if let Some(floor_offset) = config.floor_offset {
+        assert!(floor_offset > 0, "The floor must be below the lowest rock");
+        max.y += floor_offset - 1; // Leave room above the floor
+    }
 
-    let mut cave = Cave::new(min, max);
+    let mut sand_fits = false;
+    match &config.width {
+        Width::Bounded(range) => {
+            min.x = *range.start();
+            max.x = *range.end();
+        },
+        Width::Unbounded => {
+            // Make room for particles to spread as far as they could before falling out of the cave
+            let spread = max.y + 1;
+            let sources_min_x = config.sources.iter().map(|source| source.x).min().unwrap_or(min.x);
+            let sources_max_x = config.sources.iter().map(|source| source.x).max().unwrap_or(max.x);
+            if config.floor_offset.is_some() {
+                // Falling particles can't get any further from the sources than the floor is deep, and keeping
+                // the grid narrow makes a big difference to how fast it fills up
+                min.x = sources_min_x.saturating_sub(spread);
+                max.x = sources_max_x + spread;
+                sand_fits = sources_min_x >= spread;
+            } else {
+                sand_fits = min.x.min(sources_min_x) >= spread;
+                min.x = min.x.min(sources_min_x).saturating_sub(spread);
+                max.x = max.x.max(sources_max_x) + spread;
+            }
+        }
+    }
+
+    let mut cave = Cave::new(min, max, config);
+    cave.sand_fits = sand_fits && config.sources.iter().all(|source| cave.contains(source));
     input.lines().for_each(|r| parse_rock_formation(r, &mut cave));
     cave
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CaveConfig, DropResult, InvalidRulesError, MovementRules, Position2D, Width, parse_cave, parse_cave_with};
+
+    static CUP: &str = "498,2 -> 498,4 -> 502,4 -> 502,2";
+
+    #[test]
+    fn verify_water_fills_cup() {
+        // Water spreads across the bottom of the cup before overflowing the sides into the void
+        let mut cave = parse_cave_with(CUP, &CaveConfig::default());
+        assert_eq!(cave.fill(&MovementRules::water()), 6);
+        assert_eq!(cave.drop_particle(&MovementRules::water(), Position2D { x: 500, y: 0 }), DropResult::Lost);
+    }
+
+    #[test]
+    fn verify_rules() {
+        assert!(MovementRules::new(vec![(0, 1), (-2, 0), (2, 0), (1, 1)]).is_ok());
+        // Left, up then right would walk in a square forever
+        assert_eq!(MovementRules::new(vec![(-1, 0), (0, -1), (1, 0)]).unwrap_err(), InvalidRulesError::MovesUp);
+        assert_eq!(MovementRules::new(vec![(0, 1), (0, 0)]).unwrap_err(), InvalidRulesError::StandsStill);
+        assert_eq!(MovementRules::new(vec![(-1, 0), (2, 0)]).unwrap_err(), InvalidRulesError::UnevenSideways);
+
+        // Custom rules work the same as the built in ones
+        let mut cave = parse_cave_with(CUP, &CaveConfig::default());
+        assert_eq!(cave.fill(&MovementRules::new(vec![(0, 1), (-1, 0), (1, 0)]).unwrap()), 6);
+    }
+
+    #[test]
+    fn verify_sand_in_cup() {
+        // Sand piles up on the walls of the cup until it blocks the spout
+        let mut cave = parse_cave_with(CUP, &CaveConfig::default());
+        assert_eq!(cave.fill(&MovementRules::sand()), 10);
+    }
+
+    #[test]
+    fn verify_walls() {
+        // With walls right next to the spout, sand can only stack straight up
+        let config = CaveConfig { width: Width::Bounded(500..=500), ..CaveConfig::default() };
+        let mut cave = parse_cave_with(CUP, &config);
+        assert_eq!(cave.fill(&MovementRules::sand()), 4);
+    }
+
+    #[test]
+    fn verify_multiple_sources() {
+        let config = CaveConfig {
+            sources: vec![Position2D { x: 500, y: 0 }, Position2D { x: 520, y: 0 }],
+            floor_offset: Some(2),
+            width: Width::Unbounded,
+        };
+        let mut cave = parse_cave_with(super::super::INPUT_EXAMPLE, &config);
+        let single_source = super::super::part2_solve1::solve(super::super::INPUT_EXAMPLE);
+        // The two piles overlap, so they hold less than two separate piles would
+        let settled = cave.fill(&MovementRules::sand());
+        assert!(settled > single_source && settled < single_source + 121);
+    }
+
+    #[test]
+    fn verify_snapshots() {
+        // Compare against the drawings in the puzzle description
+        let expected = [
+            (1, include_str!("./snapshots/example_1.txt")),
+            (2, include_str!("./snapshots/example_2.txt")),
+            (5, include_str!("./snapshots/example_5.txt")),
+            (22, include_str!("./snapshots/example_22.txt")),
+        ];
+        let mut snapshots = Vec::new();
+        let mut cave = parse_cave(super::super::INPUT_EXAMPLE);
+        cave.fill_with_snapshots(&MovementRules::sand(), 1, |settled, cave| {
+            let section = cave.render_section(Position2D { x: 494, y: 0 }, Position2D { x: 503, y: 9 });
+            snapshots.push((settled, section));
+        });
+        for (settled, drawing) in expected {
+            assert_eq!(snapshots[settled - 1], (settled, drawing.to_string()));
+        }
+
+        // The final snapshot shows the path of the sand flowing into the abyss
+        let (settled, _) = snapshots.last().unwrap();
+        assert_eq!(*settled, 24);
+        let section = cave.render_section(Position2D { x: 493, y: 0 }, Position2D { x: 503, y: 10 });
+        assert_eq!(section, include_str!("./snapshots/example_24.txt"));
+    }
+
+    #[test]
+    fn verify_snapshot_with_floor() {
+        let mut cave = parse_cave_with(super::super::INPUT_EXAMPLE, &CaveConfig::with_floor(2));
+        let mut snapshots = 0;
+        let settled = cave.fill_with_snapshots(&MovementRules::sand(), 10, |_, _| snapshots += 1);
+        assert_eq!(settled, 93);
+        // One every 10 grains, plus the final state
+        assert_eq!(snapshots, 10);
+        let section = cave.render_section(Position2D { x: 488, y: 0 }, Position2D { x: 512, y: 11 });
+        assert_eq!(section, include_str!("./snapshots/example_floor.txt"));
+    }
+
+    #[test]
+    fn verify_render() {
+        let mut cave = parse_cave_with(CUP, &CaveConfig::default());
+        // The last grain settles on the spout, hiding it
+        let expected = [
+            ".......o.......",
+            "......ooo......",
+            ".....#ooo#.....",
+            ".....#ooo#.....",
+            ".....#####.....",
+        ];
+        cave.fill(&MovementRules::sand());
+        assert_eq!(cave.render(), expected.join("\r\n") + "\r\n");
+    }
+}
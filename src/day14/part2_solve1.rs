@@ -1,50 +1,14 @@
-use super::lib::{Position2D, Cave, parse_cave};
+use super::lib::{CaveConfig, MovementRules, parse_cave_with};
 
-fn simulate_sand(cave: &Cave, start: Position2D, floor: usize) -> Option<Position2D> {
-
-    let is_blocked = |position: &Position2D| position.y > floor || cave.is_blocked(position);
-
-    let mut sand = start;
-    loop {
-        if !is_blocked(&sand.below()) {
-            // Sand falls down
-            sand = sand.below();
-        } else if !is_blocked(&sand.left().below()) {
-            // Sand moves down-left
-            sand = sand.left().below();
-        } else if !is_blocked(&sand.right().below()) {
-            // Sand moves down-right
-            sand = sand.right().below();
-        } else if sand == start && is_blocked(&sand) {
-            // Spout is blocked
-            return None;
-        } else {
-            // Sand settles here
-            return Some(sand);
-        }
-    }
+pub fn solve(input: &str) -> usize {
+    solve_with(input, &CaveConfig::with_floor(2), &MovementRules::sand())
 }
 
-pub fn solve(input: &str) -> i32 {
-    let mut cave = parse_cave(input);
-    let floor = cave.get_lowest_level();
+pub fn solve_with(input: &str, config: &CaveConfig, rules: &MovementRules) -> usize {
+    let mut cave = parse_cave_with(input, config);
 
-    // Let the sand fall!
-    let mut settled_sands = 0;
-    let start = Position2D { x: 500, y: 0 };
-    loop {
-        if let Some(settle_at) = simulate_sand(&cave, start, floor) {
-            // Sand has settled
-            cave.mark_blocked(settle_at);
-            settled_sands += 1;
-            //println!("Settled at {:?}", settle_at);
-            //println!("{} units settled", settled_sands);
-            //cave.print(Position2D {x: 493, y: 0}, Position2D {x: 504, y: 10});
-        } else {
-            // Sand is now falling into the void
-            return settled_sands;
-        }
-    }
+    // Let the sand fall until it blocks the spout
+    cave.fill(rules)
 }
 
 pub mod tests {
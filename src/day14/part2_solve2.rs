@@ -1,73 +1,91 @@
-use super::lib::{Position2D, Cave, parse_cave};
+use super::lib::{Position2D, Cave, CaveConfig, DropResult, MovementRules, Step, parse_cave_with};
 
 struct SandSimulator<'a> {
-    cache: Vec<Position2D>,
-    sands_settled: i32,
-    floor: usize,
+    caches: Vec<Vec<Position2D>>, // Path taken by the previous particle from each source
+    sands_settled: usize,
+    rules: &'a MovementRules,
     cave: &'a mut Cave,
 }
 
 impl SandSimulator<'_> {
-    fn new(cave: &mut Cave, floor: usize) -> SandSimulator {
+    fn new<'a>(cave: &'a mut Cave, rules: &'a MovementRules) -> SandSimulator<'a> {
         SandSimulator {
-            cache: Vec::new(),
+            caches: vec![Vec::new(); cave.get_sources().len()],
             sands_settled: 0,
-            floor,
+            rules,
             cave,
         }
     }
 
-    fn drop_sand(&mut self, from: Position2D) -> Option<Position2D> {
-
-        let is_blocked = |position: &Position2D| position.y > self.floor || self.cave.is_blocked(position);
-
-        let mut sand;
-        if self.cache.first().is_some() && self.cache.first().unwrap() == &from {
-            // Existing cache exists and is valid for this starting point
-            sand = *self.cache.last().unwrap();
-        } else {
-            // Cache is empty or not valid
-            self.cache.clear();
-            self.cache.push(from);
-            sand = from;
+    fn drop_sand(&mut self, source_index: usize) -> DropResult {
+        let from = self.cave.get_sources()[source_index];
+        let cache = &mut self.caches[source_index];
+        if cache.is_empty() {
+            // Cache is empty or not valid. A valid cache starts at the source, and anything settling on the
+            // source empties it, so this is the only time the spout can be blocked.
+            if self.cave.is_blocked(&from) {
+                // Spout is blocked
+                return DropResult::SourceBlocked;
+            }
+            cache.push(from);
         }
 
+        // Continue from where the previous particle was before it settled
+        let mut sand = *cache.last().unwrap();
+        let can_turn_back = self.rules.can_turn_back();
         loop {
-            if !is_blocked(&sand.below()) {
-                // Sand falls down
-                sand = sand.below();
-                self.cache.push(sand);
-            } else if !is_blocked(&sand.left().below()) {
-                // Sand moves down-left
-                sand = sand.left().below();
-                self.cache.push(sand);
-            } else if !is_blocked(&sand.right().below()) {
-                // Sand moves down-right
-                sand = sand.right().below();
-                self.cache.push(sand);
-            } else if sand == from && is_blocked(&sand) {
-                // Spout is blocked
-                return None;
-            } else {
-                // Sand settles here
-                self.sands_settled += 1;
-                self.cave.mark_blocked(sand);
-                self.cache.pop();
-                return Some(sand);
+            let previous = if can_turn_back { cache.len().checked_sub(2).map(|i| cache[i]) } else { None };
+            match self.cave.step(self.rules, sand, previous) {
+                Step::Move(next) => {
+                    sand = next;
+                    cache.push(sand);
+                },
+                Step::Lost => return DropResult::Lost,
+                Step::Settle => {
+                    // Sand settles here
+                    self.sands_settled += 1;
+                    self.cave.mark_sand(sand);
+                    cache.pop();
+                    break;
+                }
+            }
+        }
+
+        // Paths from other sources are no longer valid past where this sand settled
+        if self.caches.len() > 1 {
+            for (i, cache) in self.caches.iter_mut().enumerate() {
+                if i == source_index {
+                    continue;
+                }
+                if let Some(i) = cache.iter().position(|position| *position == sand) {
+                    cache.truncate(i);
+                }
             }
         }
+        DropResult::Settled(sand)
     }
 }
 
-pub fn solve(input: &str) -> i32 {
-    let mut cave = parse_cave(input);
+pub fn solve(input: &str) -> usize {
+    solve_with(input, &CaveConfig::with_floor(2), &MovementRules::sand())
+}
 
-    // Let the sand fall!
-    let start = Position2D { x: 500, y: 0 };
-    let floor = cave.get_lowest_level();
-    let mut simulator = SandSimulator::new(&mut cave, floor);
+pub fn solve_with(input: &str, config: &CaveConfig, rules: &MovementRules) -> usize {
+    let mut cave = parse_cave_with(input, config);
+    let mut simulator = SandSimulator::new(&mut cave, rules);
 
-    while simulator.drop_sand(start).is_some() {}
+    // Let the sand fall from each source in turn until they're all blocked
+    let mut active_sources = (0..config.sources.len()).collect::<Vec<_>>();
+    while !active_sources.is_empty() {
+        let mut i = 0;
+        while i < active_sources.len() {
+            match simulator.drop_sand(active_sources[i]) {
+                DropResult::Settled(_) => i += 1,
+                DropResult::SourceBlocked => { active_sources.remove(i); },
+                DropResult::Lost => return simulator.sands_settled,
+            }
+        }
+    }
 
     simulator.sands_settled
 }
@@ -0,0 +1,120 @@
+use super::lib::{CaveConfig, Position2D, parse_cave_with};
+
+pub fn solve(input: &str) -> usize {
+    solve_with(input, &CaveConfig::with_floor(2))
+}
+
+// Only models sand falling onto a floor, as it relies on every reachable square filling up
+pub fn solve_with(input: &str, config: &CaveConfig) -> usize {
+    assert!(config.floor_offset.is_some(), "Sand must land on a floor to fill the cave");
+    let cave = parse_cave_with(input, config);
+    let range = cave.get_horizontal_range();
+    let (min_x, width) = (*range.start(), range.end() - range.start() + 1);
+
+    // Sand ends up everywhere below the sources that it can reach, so rather than dropping each
+    // grain we fill the cave one row at a time. A square fills if it's a source or any of the
+    // three squares above it that sand could fall from are filled, and it isn't rock.
+    let mut previous_row = vec![false; width];
+    let mut current_row = vec![false; width];
+    let mut settled_sands = 0;
+
+    for y in 0..=cave.get_lowest_level() {
+        for x in range.clone() {
+            let i = x - min_x;
+            let fed_from_above = previous_row[i]
+                || (i > 0 && previous_row[i - 1])
+                || (i + 1 < width && previous_row[i + 1]);
+            let is_source = cave.get_sources().contains(&Position2D { x, y });
+            current_row[i] = (fed_from_above || is_source) && !cave.is_blocked(&Position2D { x, y });
+            if current_row[i] {
+                settled_sands += 1;
+            }
+        }
+        std::mem::swap(&mut previous_row, &mut current_row);
+    }
+
+    settled_sands
+}
+
+pub mod tests {
+    use criterion::Criterion;
+    use std::path::Path;
+    #[cfg(test)]
+    use oorandom::Rand32;
+
+    #[cfg(test)]
+    use super::super::lib::{CaveConfig, MovementRules, Position2D, Width};
+
+    // Generate a scan of random horizontal and vertical rock paths below the spout
+    #[cfg(test)]
+    fn random_rock_layout(rng: &mut Rand32) -> String {
+        let mut output = String::new();
+        for _ in 0..rng.rand_range(1..8) {
+            let mut x = rng.rand_range(485..516);
+            let mut y = rng.rand_range(4..25);
+            output.push_str(&format!("{},{}", x, y));
+            for segment in 0..rng.rand_range(1..5) {
+                let length = rng.rand_range(0..8);
+                if segment % 2 == 0 {
+                    x = if rng.rand_range(0..2) == 0 { x - length } else { x + length };
+                } else {
+                    y = if rng.rand_range(0..2) == 0 { y.saturating_sub(length).max(4) } else { y + length };
+                }
+                output.push_str(&format!(" -> {},{}", x, y));
+            }
+            output.push_str("\r\n");
+        }
+        output
+    }
+
+    #[cfg(test)]
+    fn random_config(rng: &mut Rand32) -> CaveConfig {
+        let sources = (0..rng.rand_range(1..4))
+            .map(|_| Position2D { x: rng.rand_range(490..511) as usize, y: rng.rand_range(0..3) as usize })
+            .collect();
+        let width = if rng.rand_range(0..2) == 0 { Width::Unbounded } else { Width::Bounded(482..=518) };
+        CaveConfig { sources, floor_offset: Some(rng.rand_range(1..4) as usize), width }
+    }
+
+    #[test]
+    fn verify_example() {
+        assert_eq!(super::solve(super::super::INPUT_EXAMPLE), 93);
+    }
+
+    #[test]
+    fn verify_solution() {
+        assert_eq!(super::solve(super::super::INPUT), 22646);
+    }
+
+    #[test]
+    fn verify_random_layouts() {
+        let mut rng = Rand32::new(14);
+        for _ in 0..500 {
+            let input = random_rock_layout(&mut rng);
+            let expected = super::super::part2_solve1::solve(&input);
+            assert_eq!(super::super::part2_solve2::solve(&input), expected, "{}", input);
+            assert_eq!(super::solve(&input), expected, "{}", input);
+        }
+    }
+
+    #[test]
+    fn verify_random_configs() {
+        let mut rng = Rand32::new(32);
+        let rules = MovementRules::sand();
+        for _ in 0..500 {
+            let input = random_rock_layout(&mut rng);
+            let config = random_config(&mut rng);
+            let expected = super::super::part2_solve1::solve_with(&input, &config, &rules);
+            assert_eq!(super::super::part2_solve2::solve_with(&input, &config, &rules), expected, "{}\n{:?}", input, config);
+            assert_eq!(super::solve_with(&input, &config), expected, "{}\n{:?}", input, config);
+        }
+    }
+
+    pub fn benchmark(c: &mut Criterion) {
+        let path = Path::new(file!());
+        let day = path.parent().unwrap().to_str().unwrap();
+        let solution = path.file_stem().unwrap().to_str().unwrap();
+        let id = day.to_owned() + "_" + solution; // dayX_partY_solveZ
+        c.bench_function( &id, |b| b.iter(|| super::solve(super::super::INPUT)));
+    }
+}
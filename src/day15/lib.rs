@@ -0,0 +1,158 @@
+use itertools::Itertools;
+use regex::Regex;
+use interval::{interval_set::ToIntervalSet, IntervalSet};
+use gcollections::ops::*;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Position2D {
+    pub x: i32,
+    pub y: i32
+}
+
+impl Position2D {
+    pub fn manhattan_distance(&self, other: &Position2D) -> i32 {
+        (self.x - other.x).abs() + (self.y - other.y).abs()
+    }
+}
+
+pub struct Sensor {
+    pub position: Position2D,
+    pub nearest_beacon: Position2D
+}
+
+impl Sensor {
+    pub fn radius(&self) -> i32 {
+        self.position.manhattan_distance(&self.nearest_beacon)
+    }
+
+    pub fn covers(&self, position: &Position2D) -> bool {
+        self.position.manhattan_distance(position) <= self.radius()
+    }
+
+    // The diamond covered by the sensor is widest on its own row and narrows by one on each side
+    // for every row away from it. The same goes for columns.
+    fn find_excluded_positions(&self, midpoint: i32, distance: i32) -> Option<(i32, i32)> {
+        let width_at_target = self.radius() - distance;
+        if width_at_target < 0 {
+            None
+        } else {
+            Some((midpoint - width_at_target, midpoint + width_at_target))
+        }
+    }
+
+    pub fn find_excluded_positions_at_y(&self, y: i32) -> Option<(i32, i32)> {
+        self.find_excluded_positions(self.position.x, (self.position.y - y).abs())
+    }
+
+    pub fn find_excluded_positions_at_x(&self, x: i32) -> Option<(i32, i32)> {
+        self.find_excluded_positions(self.position.y, (self.position.x - x).abs())
+    }
+}
+
+pub struct SensorField {
+    pub sensors: Vec<Sensor>
+}
+
+impl SensorField {
+    fn union_of(intervals: impl Iterator<Item = (i32, i32)>) -> IntervalSet<i32> {
+        intervals.fold(IntervalSet::empty(), |set, interval| set.union(&interval.to_interval_set()))
+    }
+
+    // All x positions on the row that are covered by at least one sensor
+    pub fn excluded_on_row(&self, y: i32) -> IntervalSet<i32> {
+        Self::union_of(self.sensors.iter().filter_map(|sensor| sensor.find_excluded_positions_at_y(y)))
+    }
+
+    // All y positions in the column that are covered by at least one sensor
+    pub fn excluded_on_column(&self, x: i32) -> IntervalSet<i32> {
+        Self::union_of(self.sensors.iter().filter_map(|sensor| sensor.find_excluded_positions_at_x(x)))
+    }
+
+    pub fn is_covered(&self, position: &Position2D) -> bool {
+        self.sensors.iter().any(|sensor| sensor.covers(position))
+    }
+
+    // Distinct beacons which lie on the given row
+    pub fn beacons_on_row(&self, y: i32) -> Vec<Position2D> {
+        self.sensors
+            .iter()
+            .map(|sensor| sensor.nearest_beacon)
+            .filter(|beacon| beacon.y == y)
+            .unique()
+            .collect_vec()
+    }
+
+    // Number of positions in the (inclusive) box that are covered by at least one sensor
+    pub fn covered_area(&self, min: Position2D, max: Position2D) -> u64 {
+        let columns = (min.x, max.x).to_interval_set();
+        (min.y..=max.y)
+            .map(|y| self.excluded_on_row(y).intersection(&columns).size() as u64)
+            .sum()
+    }
+
+    // Every position in the (inclusive) box that isn't covered by any sensor, row by row
+    pub fn uncovered_points(&self, min: Position2D, max: Position2D) -> Vec<Position2D> {
+        let columns = (min.x, max.x).to_interval_set();
+        (min.y..=max.y)
+            .flat_map(|y| {
+                let gaps = columns.difference(&self.excluded_on_row(y));
+                gaps.iter()
+                    .flat_map(|interval| interval.lower()..=interval.upper())
+                    .map(|x| Position2D { x, y })
+                    .collect_vec()
+            })
+            .collect_vec()
+    }
+}
+
+pub fn parse_input(input: &str) -> SensorField {
+    let regex = r"^Sensor at x=(-?\d+), y=(-?\d+): closest beacon is at x=(-?\d+), y=(-?\d+)$";
+    let re = Regex::new(regex).unwrap();
+    let sensors = input.lines().map(|line| {
+        let captures = re.captures(line).unwrap();
+        Sensor {
+            position: Position2D { x: captures[1].parse().unwrap(), y: captures[2].parse().unwrap() },
+            nearest_beacon: Position2D { x: captures[3].parse().unwrap(), y: captures[4].parse().unwrap() }
+        }
+    }).collect_vec();
+    SensorField { sensors }
+}
+
+#[cfg(test)]
+mod tests {
+    use interval::interval_set::ToIntervalSet;
+    use gcollections::ops::*;
+    use super::{Position2D, parse_input};
+
+    const MIN: Position2D = Position2D { x: 0, y: 0 };
+    const MAX: Position2D = Position2D { x: 20, y: 20 };
+
+    #[test]
+    fn verify_uncovered_points() {
+        let field = parse_input(super::super::INPUT_EXAMPLE);
+        assert_eq!(field.uncovered_points(MIN, MAX), vec![Position2D { x: 14, y: 11 }]);
+        assert!(!field.is_covered(&Position2D { x: 14, y: 11 }));
+        assert!(field.is_covered(&Position2D { x: 14, y: 10 }));
+    }
+
+    #[test]
+    fn verify_covered_area() {
+        let field = parse_input(super::super::INPUT_EXAMPLE);
+        assert_eq!(field.covered_area(MIN, MAX), 21 * 21 - 1);
+        // Every sensor is beyond this corner of the example
+        let far = Position2D { x: -100, y: -100 };
+        assert_eq!(field.covered_area(far, Position2D { x: -90, y: -90 }), 0);
+    }
+
+    #[test]
+    fn verify_rows_and_columns_agree() {
+        // Counting by columns and by rows should cover the same positions
+        let field = parse_input(super::super::INPUT_EXAMPLE);
+        let rows = (0, 20).to_interval_set();
+        let by_columns = (-10..=30)
+            .map(|x| field.excluded_on_column(x).intersection(&rows).size() as u64)
+            .sum::<u64>();
+        let by_rows = field.covered_area(Position2D { x: -10, y: 0 }, Position2D { x: 30, y: 20 });
+        assert_eq!(by_columns, by_rows);
+    }
+}
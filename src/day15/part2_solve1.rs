@@ -0,0 +1,42 @@
+use interval::interval_set::ToIntervalSet;
+use gcollections::ops::*;
+use super::lib::parse_input;
+
+pub fn solve(input: &str, max_y: i32) -> usize {
+    let field = parse_input(input);
+    let columns = (0, max_y).to_interval_set();
+
+    // Check every single y level in range
+    for y in 0..=max_y {
+        let gaps = columns.difference(&field.excluded_on_row(y));
+        if let Some(gap) = gaps.iter().next() {
+            let x = gap.lower();
+            return x as usize * 4000000 + y as usize;
+        }
+    }
+
+    unreachable!();
+}
+
+pub mod tests {
+    use criterion::Criterion;
+    use std::path::Path;
+
+    #[test]
+    fn verify_example() {
+        assert_eq!(super::solve(super::super::INPUT_EXAMPLE, 20), 56000011);
+    }
+
+    #[test]
+    fn verify_solution() {
+        assert_eq!(super::solve(super::super::INPUT, 4000000), 10457634860779);
+    }
+
+    pub fn benchmark(c: &mut Criterion) {
+        let path = Path::new(file!());
+        let day = path.parent().unwrap().to_str().unwrap();
+        let solution = path.file_stem().unwrap().to_str().unwrap();
+        let id = day.to_owned() + "_" + solution; // dayX_partY_solveZ
+        c.bench_function( &id, |b| b.iter(|| super::solve(super::super::INPUT, 56000011)));
+    }
+}
\ No newline at end of file
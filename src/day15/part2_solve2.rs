@@ -0,0 +1,78 @@
+use itertools::Itertools;
+use super::lib::{Position2D, SensorField, parse_input};
+
+// The distress beacon is the only uncovered point in the search area, so it must sit right outside
+// the edge of at least one sensor's diamond (or against the edge of the search area). Each diamond
+// is bounded by two lines of constant `x + y` and two of constant `x - y`, so we only need to check
+// where those lines cross each other and the edges of the search area.
+fn find_boundary_lines(field: &SensorField) -> (Vec<i64>, Vec<i64>) {
+    let mut sums = Vec::new();
+    let mut differences = Vec::new();
+    for sensor in &field.sensors {
+        let Position2D { x, y } = sensor.position;
+        let (x, y) = (x as i64, y as i64);
+        let radius = sensor.radius() as i64;
+        sums.extend([x + y - radius - 1, x + y + radius + 1]);
+        differences.extend([x - y - radius - 1, x - y + radius + 1]);
+    }
+    (sums.into_iter().sorted().dedup().collect(), differences.into_iter().sorted().dedup().collect())
+}
+
+pub fn solve(input: &str, max_y: i32) -> usize {
+    let field = parse_input(input);
+    let max = max_y as i64;
+    let (sums, differences) = find_boundary_lines(&field);
+
+    // Where a line of each kind cross. Lines with an odd total cross between grid points.
+    let crossings = sums.iter()
+        .cartesian_product(differences.iter())
+        .filter(|(sum, difference)| (*sum + *difference) % 2 == 0)
+        .map(|(sum, difference)| ((sum + difference) / 2, (sum - difference) / 2));
+    // Where each line meets the edges of the search area, including the corners
+    let edges = [0, max];
+    let edge_crossings = edges.into_iter()
+        .flat_map(|edge| {
+            let on_sums = sums.iter().flat_map(move |sum| [(edge, sum - edge), (sum - edge, edge)]);
+            let on_differences = differences.iter().flat_map(move |difference| [(edge, edge - difference), (edge + difference, edge)]);
+            on_sums.chain(on_differences)
+        })
+        .chain(edges.into_iter().cartesian_product(edges));
+
+    let (x, y) = crossings
+        .chain(edge_crossings)
+        .filter(|(x, y)| (0..=max).contains(x) && (0..=max).contains(y))
+        .find(|(x, y)| !field.is_covered(&Position2D { x: *x as i32, y: *y as i32 }))
+        .unwrap();
+    x as usize * 4000000 + y as usize
+}
+
+pub mod tests {
+    use criterion::Criterion;
+    use std::path::Path;
+
+    #[test]
+    fn verify_example() {
+        assert_eq!(super::solve(super::super::INPUT_EXAMPLE, 20), 56000011);
+    }
+
+    #[test]
+    fn verify_solution() {
+        assert_eq!(super::solve(super::super::INPUT, 4000000), 10457634860779);
+    }
+
+    #[test]
+    fn verify_agrees_with_solve1() {
+        let example = super::super::INPUT_EXAMPLE;
+        assert_eq!(super::solve(example, 20), super::super::part2_solve1::solve(example, 20));
+        let input = super::super::INPUT;
+        assert_eq!(super::solve(input, 4000000), super::super::part2_solve1::solve(input, 4000000));
+    }
+
+    pub fn benchmark(c: &mut Criterion) {
+        let path = Path::new(file!());
+        let day = path.parent().unwrap().to_str().unwrap();
+        let solution = path.file_stem().unwrap().to_str().unwrap();
+        let id = day.to_owned() + "_" + solution; // dayX_partY_solveZ
+        c.bench_function( &id, |b| b.iter(|| super::solve(super::super::INPUT, 4000000)));
+    }
+}
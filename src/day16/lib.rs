@@ -1,5 +1,3 @@
-use std::{collections::{BinaryHeap}, cmp::Ordering};
-
 use itertools::Itertools;
 use regex::Regex;
 
@@ -15,58 +13,111 @@ pub struct Valve {
     pub links: Vec<Tunnel>
 }
 
-// From https://doc.rust-lang.org/std/collections/binary_heap/index.html#examples
-#[derive(Copy, Clone, Eq, PartialEq)]
-struct State {
-    distance: i32,
-    valve_i: usize,
+pub struct ValveGraph {
+    pub valves: Vec<Valve>,
+    distances: Vec<Vec<Option<i32>>>, // Shortest distance between every pair of valves
 }
 
-impl Ord for State {
-    fn cmp(&self, other: &Self) -> Ordering {
-        other.distance.cmp(&self.distance)
-            .then_with(|| self.valve_i.cmp(&other.valve_i))
+impl ValveGraph {
+    pub fn new(valves: Vec<Valve>) -> ValveGraph {
+        // Floyd-Warshall, starting with the direct tunnels
+        let mut distances = vec![vec![None; valves.len()]; valves.len()];
+        for (i, valve) in valves.iter().enumerate() {
+            distances[i][i] = Some(0);
+            for tunnel in &valve.links {
+                let distance = &mut distances[i][tunnel.to_valve_index];
+                *distance = Some(distance.map_or(tunnel.distance, |d: i32| d.min(tunnel.distance)));
+            }
+        }
+        for via in 0..valves.len() {
+            let from_via = distances[via].clone();
+            for row in distances.iter_mut() {
+                let Some(to_via) = row[via] else {
+                    continue;
+                };
+                for (distance, from_via) in row.iter_mut().zip(&from_via) {
+                    if let Some(from_via) = from_via {
+                        if distance.is_none_or(|d| to_via + from_via < d) {
+                            *distance = Some(to_via + from_via);
+                        }
+                    }
+                }
+            }
+        }
+        ValveGraph { valves, distances }
     }
-}
 
-impl PartialOrd for State {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
+    pub fn len(&self) -> usize {
+        self.valves.len()
     }
-}
 
-pub fn find_shortest_distance_to_valves<'a>(valves: &'a Vec<Valve>, start_valve_i: usize) -> Vec<Tunnel> {
-    let mut visitation_queue = BinaryHeap::new();
-    let mut distance_from_start = (0..valves.len()).map(|_| i32::MAX).collect_vec();
+    pub fn is_empty(&self) -> bool {
+        self.valves.is_empty()
+    }
 
-    // Find shortest path to all valves
-    visitation_queue.push(State { distance: 0, valve_i: start_valve_i });
-    distance_from_start[start_valve_i] = 0;
-    while let Some(State { distance, valve_i}) = visitation_queue.pop() {
-        if distance > distance_from_start[valve_i] {
-            continue; // Already found a better path to this node
-        }
+    pub fn find_valve_index(&self, name: &str) -> Option<usize> {
+        self.valves.iter().position(|valve| valve.name == name)
+    }
+
+    // Shortest distance between two valves, or None if there's no way to get there
+    pub fn distance(&self, from: usize, to: usize) -> Option<i32> {
+        self.distances[from][to]
+    }
+
+    // Keep only the start and the valves worth opening, with a direct tunnel between every pair
+    // that can reach each other. The start is always the first valve.
+    pub fn compress(&self, start: &str) -> ValveGraph {
+        let start_i = self.find_valve_index(start).unwrap();
+        let kept = [start_i]
+            .into_iter()
+            .chain((0..self.len()).filter(|&i| i != start_i && self.valves[i].flow_rate > 0))
+            .collect_vec();
 
-        for tunnel in &valves[valve_i].links {
-            let next = State { distance: distance + tunnel.distance, valve_i: tunnel.to_valve_index };
-            if next.distance < distance_from_start[next.valve_i] {
-                visitation_queue.push(next);
-                distance_from_start[next.valve_i] = next.distance;
+        let valves = kept
+            .iter()
+            .map(|&from| Valve {
+                name: self.valves[from].name.clone(),
+                flow_rate: self.valves[from].flow_rate,
+                links: kept
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, &to)| to != from)
+                    .filter_map(|(to_i, &to)| self.distance(from, to).map(|distance| Tunnel {
+                        distance,
+                        to_valve_index: to_i,
+                        to_valve_flow_rate: self.valves[to].flow_rate
+                    }))
+                    .collect_vec()
+            })
+            .collect_vec();
+        ValveGraph::new(valves)
+    }
+
+    // Export as a GraphViz graph. Tunnels longer than 1 minute are labelled with their length.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("graph valves {\n");
+        for valve in &self.valves {
+            dot += &format!("    {} [label=\"{}\\n{}\"];\n", valve.name, valve.name, valve.flow_rate);
+        }
+        for (from, valve) in self.valves.iter().enumerate() {
+            for tunnel in &valve.links {
+                // Tunnels go both ways, so only draw them once
+                let to = tunnel.to_valve_index;
+                let is_two_way = self.valves[to].links.iter().any(|back| back.to_valve_index == from && back.distance == tunnel.distance);
+                if is_two_way && to < from {
+                    continue;
+                }
+                let to_name = &self.valves[to].name;
+                if tunnel.distance == 1 {
+                    dot += &format!("    {} -- {};\n", valve.name, to_name);
+                } else {
+                    dot += &format!("    {} -- {} [label=\"{}\"];\n", valve.name, to_name, tunnel.distance);
+                }
             }
         }
+        dot += "}\n";
+        dot
     }
-
-    // Return shortest paths
-    return distance_from_start
-        .iter()
-        .filter(|distance| **distance != i32::MAX)
-        .enumerate()
-        .map(|(i, distance)| Tunnel {
-            distance: *distance, to_valve_index:
-            i,
-            to_valve_flow_rate: valves[i].flow_rate
-        })
-        .collect_vec()
 }
 
 pub fn parse_input(input: &str) -> Vec<Valve> {
@@ -104,4 +155,64 @@ pub fn parse_input(input: &str) -> Vec<Valve> {
         .collect_vec();
 
     indexed_valves
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ValveGraph, parse_input};
+
+    // CC and DD can't be reached from AA
+    static DISCONNECTED: &str = "Valve CC has flow rate=7; tunnel leads to valve DD\r\n\
+        Valve DD has flow rate=0; tunnel leads to valve CC\r\n\
+        Valve AA has flow rate=0; tunnels lead to valves BB\r\n\
+        Valve BB has flow rate=5; tunnels lead to valves AA";
+
+    #[test]
+    fn verify_distances() {
+        let graph = ValveGraph::new(parse_input(super::super::INPUT_EXAMPLE));
+        let aa = graph.find_valve_index("AA").unwrap();
+        let hh = graph.find_valve_index("HH").unwrap();
+        let jj = graph.find_valve_index("JJ").unwrap();
+        assert_eq!(graph.distance(aa, hh), Some(5));
+        assert_eq!(graph.distance(hh, jj), Some(7));
+        assert_eq!(graph.distance(jj, jj), Some(0));
+    }
+
+    #[test]
+    fn verify_unreachable_valves() {
+        let graph = ValveGraph::new(parse_input(DISCONNECTED));
+        assert_eq!(graph.distance(0, 1), Some(1));
+        assert_eq!(graph.distance(0, 3), None);
+        assert_eq!(graph.distance(2, 3), Some(1));
+
+        let compressed = graph.compress("AA");
+        let names = compressed.valves.iter().map(|valve| valve.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, vec!["AA", "CC", "BB"]);
+        assert_eq!(compressed.distance(0, 1), None);
+        assert_eq!(compressed.distance(0, 2), Some(1));
+        assert_eq!(super::super::part1::solve_with(DISCONNECTED, 30, "AA"), 5 * 28);
+    }
+
+    #[test]
+    fn verify_dot() {
+        let graph = ValveGraph::new(parse_input(DISCONNECTED));
+        assert_eq!(graph.to_dot(), [
+            "graph valves {",
+            "    CC [label=\"CC\\n7\"];",
+            "    DD [label=\"DD\\n0\"];",
+            "    AA [label=\"AA\\n0\"];",
+            "    BB [label=\"BB\\n5\"];",
+            "    CC -- DD;",
+            "    AA -- BB;",
+            "}",
+            "",
+        ].join("\n"));
+
+        let compressed = ValveGraph::new(parse_input(super::super::INPUT_EXAMPLE)).compress("AA");
+        let dot = compressed.to_dot();
+        assert!(dot.contains("    AA -- BB;\n"));
+        assert!(dot.contains("    AA -- HH [label=\"5\"];\n"));
+        // Every pair of the 7 kept valves is linked once
+        assert_eq!(dot.matches(" -- ").count(), 7 * 6 / 2);
+    }
+}
@@ -1,23 +1,25 @@
 use itertools::Itertools;
-use super::lib::{Tunnel, parse_input, find_shortest_distance_to_valves};
+use super::lib::{ValveGraph, parse_input};
 
-fn find_highest_pressure_release(tunnels_to_valves: &Vec<Vec<Tunnel>>, next_candidates: Vec<usize>, prev_i: usize, time_remaining: i32) -> i32 {
+fn find_highest_pressure_release(graph: &ValveGraph, next_candidates: Vec<usize>, prev_i: usize, time_remaining: i32) -> i32 {
     if time_remaining < 2 {
         return 0; // There isn't enough time to move to another valve (>= 1 minute) and open it (1 minute)
     }
 
     let mut highest_pressure_released = 0;
     for &next_i in &next_candidates {
-        let tunnel = &tunnels_to_valves[prev_i][next_i];
-        let time_remaining_after_next_valve = time_remaining - tunnel.distance - 1; // Include 1 turn to open the next valve
+        let Some(distance) = graph.distance(prev_i, next_i) else {
+            continue; // There's no way to get to this valve
+        };
+        let time_remaining_after_next_valve = time_remaining - distance - 1; // Include 1 turn to open the next valve
         if time_remaining_after_next_valve <= 0 {
             continue; // There isn't enough time for the valve to relieve any pressure once opened
         }
 
         let next_path_candidates = next_candidates.iter().filter(|candidate_i| **candidate_i != next_i).copied().collect_vec();
-        let pressure_release_from_this_valve = time_remaining_after_next_valve * tunnel.to_valve_flow_rate;
+        let pressure_release_from_this_valve = time_remaining_after_next_valve * graph.valves[next_i].flow_rate;
 
-        let pressure_released_from_remaining_path = find_highest_pressure_release( tunnels_to_valves, next_path_candidates, next_i, time_remaining_after_next_valve);
+        let pressure_released_from_remaining_path = find_highest_pressure_release( graph, next_path_candidates, next_i, time_remaining_after_next_valve);
         let pressure_released = pressure_release_from_this_valve + pressure_released_from_remaining_path;
         highest_pressure_released = highest_pressure_released.max(pressure_released)
     }
@@ -26,22 +28,15 @@ fn find_highest_pressure_release(tunnels_to_valves: &Vec<Vec<Tunnel>>, next_cand
 }
 
 pub fn solve(input: &str) -> i32 {
-    let valves = parse_input(input);
-
-    let tunnels_to_valves = (0..valves.len())
-        .map(|i| find_shortest_distance_to_valves(&valves, i))
-        .collect_vec();
-
-    let useful_valves = valves
-        .iter()
-        .enumerate()
-        .filter_map(|(i, valve)| if &valve.name == "AA" || valve.flow_rate == 0 { None } else {Some(i)})
-        .collect_vec();
+    solve_with(input, 30, "AA")
+}
 
-    let start_i = valves.iter().find_position(|valve| valve.name == "AA").unwrap().0;
-    
+pub fn solve_with(input: &str, minutes: i32, start: &str) -> i32 {
+    // Only travel between the start and the valves worth opening
+    let graph = ValveGraph::new(parse_input(input)).compress(start);
+    let useful_valves = (0..graph.len()).filter(|&i| graph.valves[i].flow_rate > 0).collect_vec();
 
-    find_highest_pressure_release( &tunnels_to_valves, useful_valves, start_i, 30)
+    find_highest_pressure_release( &graph, useful_valves, 0, minutes)
 }
 
 pub mod tests {
@@ -53,6 +48,12 @@ pub mod tests {
         assert_eq!(super::solve(super::super::INPUT_EXAMPLE), 1651);
     }
 
+    #[test]
+    fn verify_other_start() {
+        // Starting at a valve with flow lets us open it first
+        assert_eq!(super::solve_with(super::super::INPUT_EXAMPLE, 3, "BB"), 13 * 2);
+    }
+
     #[test]
     fn verify_solution() {
         assert_eq!(super::solve(super::super::INPUT), 1716);
@@ -1,6 +1,6 @@
 use std::fmt::Debug;
 use itertools::Itertools;
-use super::lib::{Tunnel, parse_input, find_shortest_distance_to_valves};
+use super::lib::{ValveGraph, parse_input};
 
 #[derive(Debug, Clone, PartialEq, PartialOrd, Eq, Ord)]
 struct Path {
@@ -8,7 +8,7 @@ struct Path {
     visits: Vec<usize>
 }
 
-fn enumerate_all_paths(tunnels_to_valves: &Vec<Vec<Tunnel>>, next_candidates: Vec<usize>, current_path: &mut Vec<usize>, prev_i: usize, acc_pressure_release: i32, time_remaining: i32) -> Vec<Path> {
+fn enumerate_all_paths(graph: &ValveGraph, next_candidates: Vec<usize>, current_path: &mut Vec<usize>, prev_i: usize, acc_pressure_release: i32, time_remaining: i32) -> Vec<Path> {
     if time_remaining < 2 || next_candidates.is_empty() {
         // !! Early exit !!
         // This path has become terminal - there are no valid candidates to explore
@@ -17,18 +17,20 @@ fn enumerate_all_paths(tunnels_to_valves: &Vec<Vec<Tunnel>>, next_candidates: Ve
 
     let mut all_subpaths = Vec::new();
     for &next_i in &next_candidates {
-        let tunnel = &tunnels_to_valves[prev_i][next_i];
-        let time_remaining_after_next_valve = time_remaining - tunnel.distance - 1; // Include 1 turn to open the next valve
+        let Some(distance) = graph.distance(prev_i, next_i) else {
+            continue; // There's no way to get to this valve
+        };
+        let time_remaining_after_next_valve = time_remaining - distance - 1; // Include 1 turn to open the next valve
         if time_remaining_after_next_valve <= 0 {
             continue; // There isn't enough time for the valve to relieve any pressure once opened
         }
 
-        let pressure_release_from_this_valve = time_remaining_after_next_valve * tunnel.to_valve_flow_rate;
+        let pressure_release_from_this_valve = time_remaining_after_next_valve * graph.valves[next_i].flow_rate;
         let acc_pressure_release_at_next = pressure_release_from_this_valve + acc_pressure_release;
         current_path.push(next_i);
         let next_path_candidates = next_candidates.iter().filter(|candidate_i| **candidate_i != next_i).copied().collect_vec();
         let mut paths = enumerate_all_paths(
-            tunnels_to_valves,
+            graph,
             next_path_candidates,
             current_path,
             next_i,
@@ -45,26 +47,21 @@ fn enumerate_all_paths(tunnels_to_valves: &Vec<Vec<Tunnel>>, next_candidates: Ve
 }
 
 pub fn solve(input: &str) -> i32 {
-    let valves = parse_input(input);
-
-    let tunnels_to_valves = (0..valves.len())
-        .map(|i| find_shortest_distance_to_valves(&valves, i))
-        .collect_vec();
+    solve_with(input, 26, "AA")
+}
 
-    let useful_valves = valves
-        .iter()
-        .enumerate()
-        .filter_map(|(i, valve)| if &valve.name == "AA" || valve.flow_rate == 0 { None } else {Some(i)})
-        .collect_vec();
+pub fn solve_with(input: &str, minutes: i32, start: &str) -> i32 {
+    // Only travel between the valves worth opening
+    let graph = ValveGraph::new(parse_input(input)).compress(start);
+    let useful_valves = (1..graph.len()).collect_vec(); // Everything but the start
 
-    let start_i = valves.iter().find_position(|valve| valve.name == "AA").unwrap().0;
     let mut paths = enumerate_all_paths(
-        &tunnels_to_valves,
+        &graph,
         useful_valves.clone(),
         &mut Vec::with_capacity(useful_valves.len()),
-        start_i,
         0,
-        26);
+        0,
+        minutes);
     paths.sort();
     paths.reverse(); // Largest score first
 
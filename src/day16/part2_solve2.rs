@@ -0,0 +1,28 @@
+use super::planner::solve_with;
+
+pub fn solve(input: &str) -> i32 {
+    solve_with(input, 2, 26, "AA").score
+}
+
+pub mod tests {
+    use criterion::Criterion;
+    use std::path::Path;
+
+    #[test]
+    fn verify_example() {
+        assert_eq!(super::solve(super::super::INPUT_EXAMPLE), 1707);
+    }
+
+    #[test]
+    fn verify_solution() {
+        assert_eq!(super::solve(super::super::INPUT), 2504);
+    }
+
+    pub fn benchmark(c: &mut Criterion) {
+        let path = Path::new(file!());
+        let day = path.parent().unwrap().to_str().unwrap();
+        let solution = path.file_stem().unwrap().to_str().unwrap();
+        let id = day.to_owned() + "_" + solution; // dayX_partY_solveZ
+        c.bench_function( &id, |b| b.iter(|| super::solve(super::super::INPUT)));
+    }
+}
@@ -0,0 +1,223 @@
+use std::collections::HashMap;
+use itertools::Itertools;
+use super::lib::{ValveGraph, parse_input};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ValveOpening {
+    pub valve: String,
+    pub minute: i32, // The minute during which the valve is opened. It releases pressure from the next minute.
+}
+
+#[derive(Debug)]
+pub struct Plan {
+    pub score: i32,
+    pub schedules: Vec<Vec<ValveOpening>>, // The valves each agent opens, in order
+}
+
+// Searches every order of opening the useful valves, keeping the best pressure release (and the
+// order that achieved it) for each set of opened valves. Valve sets are bitmasks over the useful valves.
+struct Search {
+    distances: Vec<Vec<i32>>, // Between useful valves, with the start as the last index
+    flow_rates: Vec<i32>,
+    best_at_state: HashMap<(usize, u32, i32), i32>, // (position, opened, time remaining)
+    best_per_set: Vec<i32>,
+    best_path_per_set: Vec<Vec<(usize, i32)>>, // (valve, time remaining once opened)
+    path: Vec<(usize, i32)>,
+}
+
+impl Search {
+    fn visit(&mut self, position: usize, opened: u32, time_remaining: i32, pressure_released: i32) {
+        // Another order of opening the same valves got here at the same time with a better score
+        let best = self.best_at_state.entry((position, opened, time_remaining)).or_insert(-1);
+        if *best >= pressure_released {
+            return;
+        }
+        *best = pressure_released;
+
+        if pressure_released > self.best_per_set[opened as usize] {
+            self.best_per_set[opened as usize] = pressure_released;
+            self.best_path_per_set[opened as usize] = self.path.clone();
+        }
+
+        for next in 0..self.flow_rates.len() {
+            if opened & (1 << next) != 0 {
+                continue;
+            }
+            let time_remaining_after_next_valve = time_remaining - self.distances[position][next] - 1; // Include 1 turn to open the next valve
+            if time_remaining_after_next_valve <= 0 {
+                continue; // There isn't enough time for the valve to relieve any pressure once opened
+            }
+            let pressure_release_from_next_valve = time_remaining_after_next_valve * self.flow_rates[next];
+            self.path.push((next, time_remaining_after_next_valve));
+            self.visit(next, opened | (1 << next), time_remaining_after_next_valve, pressure_released + pressure_release_from_next_valve);
+            self.path.pop();
+        }
+    }
+}
+
+// Make each entry the best score of any subset of its valves, so it's the best an agent can do when
+// limited to that set of valves. Returns the subset that achieved each score.
+fn propagate_to_supersets(best_per_set: &mut [i32], num_valves: usize) -> Vec<usize> {
+    let mut best_subset = (0..best_per_set.len()).collect_vec();
+    for valve in 0..num_valves {
+        for set in 0..best_per_set.len() {
+            if set & (1 << valve) != 0 && best_per_set[set ^ (1 << valve)] > best_per_set[set] {
+                best_per_set[set] = best_per_set[set ^ (1 << valve)];
+                best_subset[set] = best_subset[set ^ (1 << valve)];
+            }
+        }
+    }
+    best_subset
+}
+
+// Agents can't open the same valve twice, so they split the valves between them. Adding one agent
+// at a time, try every way of splitting each set between the agents so far and the new one.
+// Returns the best score and the set of valves given to each agent.
+fn combine_agents(best_per_set: &[i32], agents: usize) -> (i32, Vec<usize>) {
+    let all_valves = best_per_set.len() - 1;
+    let best_split = |best_so_far: &[i32], set: usize| {
+        // Walk every subset of the set, as the share of the new agent
+        let mut best = (best_so_far[set] + best_per_set[0], 0);
+        let mut subset = set;
+        while subset > 0 {
+            best = best.max((best_so_far[set ^ subset] + best_per_set[subset], subset));
+            subset = (subset - 1) & set;
+        }
+        best
+    };
+
+    let mut best_so_far = best_per_set.to_vec();
+    let mut splits = Vec::new();
+    for _ in 2..agents {
+        let (scores, shares): (Vec<_>, Vec<_>) = (0..best_per_set.len()).map(|set| best_split(&best_so_far, set)).unzip();
+        best_so_far = scores;
+        splits.push(shares);
+    }
+    if agents == 1 {
+        return (best_so_far[all_valves], vec![all_valves]);
+    }
+
+    // We only care about the final split across all the valves. Then work back through the
+    // earlier splits to find each agent's share.
+    let (score, last_share) = best_split(&best_so_far, all_valves);
+    let mut shares = vec![last_share];
+    let mut remaining = all_valves ^ last_share;
+    for split in splits.iter().rev() {
+        shares.push(split[remaining]);
+        remaining ^= split[remaining];
+    }
+    shares.push(remaining);
+    shares.reverse();
+    (score, shares)
+}
+
+// Find the most pressure that the agents can release together within the time limit, starting at
+// the given valve, along with the valves each of them should open
+pub fn solve_with(input: &str, agents: usize, minutes: i32, start: &str) -> Plan {
+    assert!(agents > 0);
+    // Only travel between the start and the valves worth opening
+    let graph = ValveGraph::new(parse_input(input)).compress(start);
+    let useful_valves = (0..graph.len()).filter(|&i| graph.valves[i].flow_rate > 0).collect_vec();
+    let positions = useful_valves.iter().copied().chain([0]).collect_vec();
+    let distances = positions
+        .iter()
+        .map(|&from| positions
+            .iter()
+            // Valves we can't get to are too far away to ever open
+            .map(|&to| graph.distance(from, to).unwrap_or(i32::MAX / 2))
+            .collect_vec())
+        .collect_vec();
+
+    let num_sets = 1 << useful_valves.len();
+    let mut search = Search {
+        distances,
+        flow_rates: useful_valves.iter().map(|&i| graph.valves[i].flow_rate).collect_vec(),
+        best_at_state: HashMap::new(),
+        best_per_set: vec![0; num_sets],
+        best_path_per_set: vec![Vec::new(); num_sets],
+        path: Vec::new(),
+    };
+    search.visit(useful_valves.len(), 0, minutes, 0);
+
+    let best_subset = propagate_to_supersets(&mut search.best_per_set, useful_valves.len());
+    let (score, shares) = combine_agents(&search.best_per_set, agents);
+    let schedules = shares
+        .iter()
+        .map(|&share| {
+            search.best_path_per_set[best_subset[share]]
+                .iter()
+                .map(|&(valve, time_remaining)| ValveOpening {
+                    valve: graph.valves[useful_valves[valve]].name.clone(),
+                    minute: minutes - time_remaining,
+                })
+                .collect_vec()
+        })
+        .collect_vec();
+    Plan { score, schedules }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+    use super::super::lib::parse_input;
+    use super::{Plan, solve_with};
+
+    fn openings(schedule: &[super::ValveOpening]) -> Vec<(&str, i32)> {
+        schedule.iter().map(|opening| (opening.valve.as_str(), opening.minute)).collect_vec()
+    }
+
+    // Add up the pressure released by following the schedules, checking each valve is opened once
+    fn score(input: &str, plan: &Plan, minutes: i32) -> i32 {
+        let valves = parse_input(input);
+        let opened = plan.schedules.iter().flatten().collect_vec();
+        assert!(opened.iter().map(|opening| &opening.valve).all_unique());
+        opened.iter()
+            .map(|opening| {
+                let valve = valves.iter().find(|valve| valve.name == opening.valve).unwrap();
+                valve.flow_rate * (minutes - opening.minute)
+            })
+            .sum()
+    }
+
+    #[test]
+    fn verify_single_agent() {
+        let plan = solve_with(super::super::INPUT_EXAMPLE, 1, 30, "AA");
+        assert_eq!(plan.score, 1651);
+        assert_eq!(openings(&plan.schedules[0]), vec![("DD", 2), ("BB", 5), ("JJ", 9), ("HH", 17), ("EE", 21), ("CC", 24)]);
+        assert_eq!(solve_with(super::super::INPUT, 1, 30, "AA").score, 1716);
+    }
+
+    #[test]
+    fn verify_two_agents() {
+        let plan = solve_with(super::super::INPUT_EXAMPLE, 2, 26, "AA");
+        assert_eq!(plan.score, 1707);
+        let schedules = plan.schedules.iter().map(|schedule| openings(schedule)).sorted().collect_vec();
+        assert_eq!(schedules, vec![
+            vec![("DD", 2), ("HH", 7), ("EE", 11)],
+            vec![("JJ", 3), ("BB", 7), ("CC", 9)],
+        ]);
+        assert_eq!(score(super::super::INPUT_EXAMPLE, &plan, 26), 1707);
+    }
+
+    #[test]
+    fn verify_more_agents() {
+        // More agents can't do worse, but eventually there are more agents than valves worth opening
+        let plans = (1..=5).map(|agents| solve_with(super::super::INPUT, agents, 26, "AA")).collect_vec();
+        assert!(plans.windows(2).all(|pair| pair[0].score <= pair[1].score));
+        assert_eq!(plans[1].score, 2504);
+        for (agents, plan) in plans.iter().enumerate() {
+            assert_eq!(plan.schedules.len(), agents + 1);
+            assert_eq!(score(super::super::INPUT, plan, 26), plan.score);
+        }
+        let example = (1..=7).map(|agents| solve_with(super::super::INPUT_EXAMPLE, agents, 26, "AA").score).collect_vec();
+        assert_eq!(example[5], example[6]);
+    }
+
+    #[test]
+    fn verify_other_start() {
+        // Starting at a valve with flow lets us open it first
+        let plan = solve_with(super::super::INPUT_EXAMPLE, 1, 3, "BB");
+        assert_eq!(openings(&plan.schedules[0]), vec![("BB", 1)]);
+        assert_eq!(plan.score, 13 * 2);
+    }
+}
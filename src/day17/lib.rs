@@ -0,0 +1,466 @@
+use std::collections::VecDeque;
+use itertools::Itertools;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position2D {
+    pub x: i32,
+    pub y: i32
+}
+
+impl Position2D {
+    fn move_dir(&self, direction: Direction) -> Position2D {
+        match direction {
+            Direction::Left => Position2D { x: self.x - 1, y: self.y },
+            Direction::Right => Position2D { x: self.x + 1, y: self.y },
+            Direction::Down => Position2D { x: self.x, y: self.y - 1 },
+        }
+    }
+}
+
+// Each row of the rock is a bitmask, where bit x is set if column x (from the left) is part of the rock
+#[derive(Clone)]
+pub struct Rock {
+    pub rows: Vec<u8>, // Bottom row first
+    pub width: i32
+}
+
+impl Rock {
+    // Parse a rock drawn as in the puzzle description, e.g. `.#.` over `###`
+    pub fn parse(text: &str) -> Rock {
+        // Check the width before building the rows, so a wide rock can't overflow the shift
+        let width = text.lines().map(|line| line.trim_end_matches('.').len()).max().unwrap();
+        assert!(width <= 8, "Rocks are stored as bytes");
+        let rows_from_top = text.lines()
+            .map(|line| line.chars()
+                .enumerate()
+                .filter(|(_, c)| *c == '#')
+                .fold(0u8, |row, (x, _)| row | 1 << x))
+            .collect_vec();
+        Rock { rows: rows_from_top.into_iter().rev().collect_vec(), width: width as i32 }
+    }
+
+    // The rows of the rock once it's been moved to the given column
+    fn get_offset_rows(&self, x: i32) -> impl Iterator<Item=u8> + '_ {
+        self.rows.iter().map(move |row| row << x)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    Left,
+    Right,
+    Down
+}
+
+pub struct ChamberConfig {
+    pub width: usize,
+    pub spawn_offset_x: i32, // From the left wall
+    pub spawn_offset_y: i32, // Gap between the tower and the bottom of a new rock
+}
+
+impl Default for ChamberConfig {
+    fn default() -> Self {
+        ChamberConfig { width: 7, spawn_offset_x: 2, spawn_offset_y: 3 }
+    }
+}
+
+pub struct Chamber<'a> {
+    config: &'a ChamberConfig,
+    full_row: u8,
+    highest_free_row: i32,
+    lowest_filled_row: i32,
+    formations: VecDeque<u8>, // One bitmask per row
+}
+
+impl<'a> Chamber<'a> {
+    pub fn new(config: &'a ChamberConfig) -> Chamber<'a> {
+        assert!(config.width <= 8, "Rows are stored as bytes");
+        Chamber {
+            config,
+            full_row: ((1u16 << config.width) - 1) as u8,
+            highest_free_row: 0,
+            lowest_filled_row: -1,
+            formations: VecDeque::new()
+        }
+    }
+
+    pub fn get_row(&self, y: i32) -> u8 {
+        if y >= self.highest_free_row {
+            return 0;
+        }
+
+        if y <= self.lowest_filled_row {
+            // We assume everything below a certain point is filled anyway
+            return u8::MAX;
+        }
+
+        self.formations[(y - self.lowest_filled_row - 1) as usize]
+    }
+
+    fn mark_formation_in_row(&mut self, y: i32, row: u8) {
+        // Check if we have enough space on top
+        if y >= self.highest_free_row {
+            // We must first allocate more room in the chamber
+            let rows_to_add = y + 1 - self.highest_free_row;
+            self.highest_free_row = y + 1;
+            for _ in 0..rows_to_add {
+                self.formations.push_back(0)
+            }
+        }
+
+        // Mark formation
+        self.formations[(y - self.lowest_filled_row - 1) as usize] |= row;
+    }
+
+    // Find the empty squares that a falling rock could still get to, from the top of the tower down.
+    // Rocks only move left, right and down, so a single pass from the top fills each row from the
+    // reachable squares above it, then spreads sideways through the empty squares.
+    // Returns a bitmask per row, from the top of the tower down to the lowest reachable row.
+    pub fn find_surface_profile(&self) -> Vec<u8> {
+        let mut profile = Vec::new();
+        let mut reachable = self.full_row; // Above the tower
+        for y in (self.lowest_filled_row + 1..self.highest_free_row).rev() {
+            let empty = !self.get_row(y) & self.full_row;
+            reachable &= empty;
+            loop {
+                let spread = (reachable | reachable << 1 | reachable >> 1) & empty;
+                if spread == reachable {
+                    break;
+                }
+                reachable = spread;
+            }
+            if reachable == 0 {
+                break;
+            }
+            profile.push(reachable);
+        }
+        profile
+    }
+
+    // Drop the rows below the surface profile, as no rock can reach them anymore
+    pub fn drop_unreachable_rows(&mut self, profile: &[u8]) {
+        let new_lowest_filled_row = self.highest_free_row - 1 - profile.len() as i32;
+        let rows_to_drop = new_lowest_filled_row - self.lowest_filled_row;
+        if rows_to_drop > 0 {
+            self.formations.drain(..rows_to_drop as usize);
+            self.lowest_filled_row = new_lowest_filled_row;
+        }
+    }
+
+    pub fn rock_fits_at(&self, rock: &Rock, bottom_left_pos: &Position2D) -> bool {
+        // Is rock outside the bounds of the chamber?
+        if bottom_left_pos.x < 0 { return false; }    // Too far left
+        if bottom_left_pos.y < 0 { return false; }    // Too far down
+        if bottom_left_pos.x + rock.width > self.config.width as i32 { return false; } // Too far right
+
+        // Is rock above all existing formations?
+        if bottom_left_pos.y >= self.highest_free_row {
+            return true
+        }
+
+        // Is rock obstructed by an existing settled formation?
+        rock.get_offset_rows(bottom_left_pos.x)
+            .zip(bottom_left_pos.y..)
+            .all(|(rock_row, y)| rock_row & self.get_row(y) == 0)
+    }
+
+    fn stamp_formation(&mut self, rock: &Rock, bottom_left_pos: &Position2D) {
+        for (rock_row, y) in rock.get_offset_rows(bottom_left_pos.x).zip(bottom_left_pos.y..) {
+            self.mark_formation_in_row(y, rock_row);
+        }
+    }
+
+    pub fn get_height(&self) -> i32 {
+        self.highest_free_row
+    }
+
+    // Draw the chamber as in the puzzle description, along with the falling rock (if any).
+    // The floor is only drawn if we haven't dropped any rows from the bottom.
+    pub fn render(&self, falling: Option<(&Rock, Position2D)>) -> String {
+        let falling_rows = |y: i32| falling.map_or(0, |(rock, position)| {
+            let row = y - position.y;
+            if row < 0 || row >= rock.rows.len() as i32 { 0 } else { rock.rows[row as usize] << position.x }
+        });
+        let top = falling.map_or(self.highest_free_row, |(rock, position)| position.y + rock.rows.len() as i32)
+            .max(self.highest_free_row);
+
+        let mut output = String::new();
+        for y in (self.lowest_filled_row + 1..top).rev() {
+            output.push('|');
+            for x in 0..self.config.width {
+                if falling_rows(y) & (1 << x) != 0 {
+                    output.push('@');
+                } else if self.get_row(y) & (1 << x) != 0 {
+                    output.push('#');
+                } else {
+                    output.push('.');
+                }
+            }
+            output.push_str("|\r\n");
+        }
+        if self.lowest_filled_row < 0 {
+            output.push('+');
+            output.push_str(&"-".repeat(self.config.width));
+            output.push_str("+\r\n");
+        }
+        output
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Event {
+    Spawned { rock_index: usize, position: Position2D },
+    Pushed { direction: Direction, moved: bool, position: Position2D }, // Position after the push
+    Fell { position: Position2D },
+    Settled { rock_index: usize, position: Position2D },
+}
+
+// The rock currently falling, and whether it's due to be pushed by a jet or to fall next
+#[derive(Clone, Copy)]
+struct FallingRock {
+    rock_index: usize,
+    position: Position2D,
+    pushed: bool,
+}
+
+// Drops rocks into the chamber one step at a time, cycling through the rocks and gas jets
+pub struct Simulation<'a> {
+    pub chamber: Chamber<'a>,
+    rocks: Vec<Rock>,
+    gas_jets: Vec<Direction>,
+    next_rock_index: usize,
+    jet_index: usize,
+    falling: Option<FallingRock>,
+}
+
+impl<'a> Simulation<'a> {
+    pub fn new(rocks: Vec<Rock>, gas_jets: Vec<Direction>, config: &'a ChamberConfig) -> Simulation<'a> {
+        // Every rock has to fit between the walls where it appears, and can't overlap the tower
+        assert!(config.spawn_offset_x >= 0, "Rocks must spawn inside the chamber");
+        assert!(config.spawn_offset_y >= 0, "Rocks must spawn above the tower");
+        assert!(
+            rocks.iter().all(|rock| (config.spawn_offset_x + rock.width) as usize <= config.width),
+            "Rocks must fit in the chamber at the spawn offset");
+        Simulation {
+            chamber: Chamber::new(config),
+            rocks,
+            gas_jets,
+            next_rock_index: 0,
+            jet_index: 0,
+            falling: None,
+        }
+    }
+
+    // Index of the next rock to spawn
+    pub fn get_rock_index(&self) -> usize {
+        self.next_rock_index
+    }
+
+    // Index of the next gas jet to push a rock
+    pub fn get_jet_index(&self) -> usize {
+        self.jet_index
+    }
+
+    pub fn get_falling_rock(&self) -> Option<(&Rock, Position2D)> {
+        self.falling.map(|falling| (&self.rocks[falling.rock_index], falling.position))
+    }
+
+    // Advance by a single step: spawning a rock, pushing it with gas, or letting it fall (or settle)
+    #[inline]
+    pub fn step(&mut self) -> Event {
+        let Some(mut falling) = self.falling else {
+            let rock_index = self.next_rock_index;
+            self.next_rock_index = (self.next_rock_index + 1) % self.rocks.len();
+            let position = Position2D {
+                x: self.chamber.config.spawn_offset_x,
+                y: self.chamber.highest_free_row + self.chamber.config.spawn_offset_y
+            };
+            self.falling = Some(FallingRock { rock_index, position, pushed: false });
+            return Event::Spawned { rock_index, position };
+        };
+        let rock = &self.rocks[falling.rock_index];
+
+        if !falling.pushed {
+            // Push with gas (if it can move in that direction)
+            let direction = self.gas_jets[self.jet_index];
+            self.jet_index = (self.jet_index + 1) % self.gas_jets.len();
+            let candidate_pos = falling.position.move_dir(direction);
+            let moved = self.chamber.rock_fits_at(rock, &candidate_pos);
+            if moved {
+                falling.position = candidate_pos;
+            }
+            falling.pushed = true;
+            self.falling = Some(falling);
+            return Event::Pushed { direction, moved, position: falling.position };
+        }
+
+        // Attempt to drop the rock
+        let candidate_pos = falling.position.move_dir(Direction::Down);
+        if self.chamber.rock_fits_at(rock, &candidate_pos) {
+            falling.position = candidate_pos;
+            falling.pushed = false;
+            self.falling = Some(falling);
+            Event::Fell { position: candidate_pos }
+        } else {
+            // We can't move down any further
+            self.chamber.stamp_formation(rock, &falling.position);
+            self.falling = None;
+            Event::Settled { rock_index: falling.rock_index, position: falling.position }
+        }
+    }
+
+    // Step until the next rock settles, and return where it came to rest
+    pub fn drop_rock(&mut self) -> Position2D {
+        loop {
+            if let Event::Settled { position, .. } = self.step() {
+                return position;
+            }
+        }
+    }
+}
+
+// Rocks are drawn one after the other, separated by blank lines
+pub fn parse_rocks(text: &str) -> Vec<Rock> {
+    text.replace("\r\n", "\n")
+        .split("\n\n")
+        .filter(|rock| !rock.trim().is_empty())
+        .map(Rock::parse)
+        .collect_vec()
+}
+
+pub fn parse_gas_jets(input: &str) -> Vec<Direction> {
+    input.chars().map(|c| match c {
+        '<' => Direction::Left,
+        '>' => Direction::Right,
+        _ => unreachable!()
+    }).collect_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ChamberConfig, Chamber, Direction, Event, Position2D, Rock, Simulation, parse_gas_jets, parse_rocks};
+
+    fn example_simulation(config: &ChamberConfig) -> Simulation<'_> {
+        Simulation::new(parse_rocks(super::super::ROCKS), parse_gas_jets(super::super::INPUT_EXAMPLE), config)
+    }
+
+    #[test]
+    fn verify_parse_rocks() {
+        let rocks = parse_rocks(super::super::ROCKS);
+        let shapes = rocks.iter().map(|rock| (rock.rows.clone(), rock.width)).collect::<Vec<_>>();
+        assert_eq!(shapes, vec![
+            (vec![0b1111], 4),
+            (vec![0b010, 0b111, 0b010], 3),
+            (vec![0b111, 0b100, 0b100], 3), // Bottom row first, with the leftmost column as the lowest bit
+            (vec![0b1, 0b1, 0b1, 0b1], 1),
+            (vec![0b11, 0b11], 2),
+        ]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Rocks are stored as bytes")]
+    fn verify_rock_too_wide() {
+        Rock::parse("#########");
+    }
+
+    #[test]
+    #[should_panic(expected = "Rocks must fit in the chamber at the spawn offset")]
+    fn verify_spawn_too_far_right() {
+        // The horizontal line rock is 4 wide, so only fits up to 3 from the left wall
+        let config = ChamberConfig { spawn_offset_x: 4, ..ChamberConfig::default() };
+        example_simulation(&config);
+    }
+
+    #[test]
+    #[should_panic(expected = "Rocks must spawn above the tower")]
+    fn verify_spawn_inside_tower() {
+        let config = ChamberConfig { spawn_offset_y: -1, ..ChamberConfig::default() };
+        example_simulation(&config);
+    }
+
+    #[test]
+    fn verify_first_rock_events() {
+        // As described in the puzzle
+        let config = ChamberConfig::default();
+        let mut simulation = example_simulation(&config);
+        let position = |x, y| Position2D { x, y };
+        let events = (0..9).map(|_| simulation.step()).collect::<Vec<_>>();
+        assert_eq!(events, vec![
+            Event::Spawned { rock_index: 0, position: position(2, 3) },
+            Event::Pushed { direction: Direction::Right, moved: true, position: position(3, 3) },
+            Event::Fell { position: position(3, 2) },
+            Event::Pushed { direction: Direction::Right, moved: false, position: position(3, 2) },
+            Event::Fell { position: position(3, 1) },
+            Event::Pushed { direction: Direction::Right, moved: false, position: position(3, 1) },
+            Event::Fell { position: position(3, 0) },
+            Event::Pushed { direction: Direction::Left, moved: true, position: position(2, 0) },
+            Event::Settled { rock_index: 0, position: position(2, 0) },
+        ]);
+        assert_eq!(simulation.get_rock_index(), 1);
+        assert_eq!(simulation.get_jet_index(), 4);
+    }
+
+    #[test]
+    fn verify_render() {
+        let config = ChamberConfig::default();
+        let mut simulation = example_simulation(&config);
+        simulation.drop_rock();
+        simulation.step(); // Spawn the second rock
+        let expected = [
+            "|...@...|",
+            "|..@@@..|",
+            "|...@...|",
+            "|.......|",
+            "|.......|",
+            "|.......|",
+            "|..####.|",
+            "+-------+",
+            "",
+        ];
+        assert_eq!(simulation.chamber.render(simulation.get_falling_rock()), expected.join("\r\n"));
+
+        for _ in 1..10 {
+            simulation.drop_rock();
+        }
+        let expected = [
+            "|....#..|",
+            "|....#..|",
+            "|....##.|",
+            "|##..##.|",
+            "|######.|",
+            "|.###...|",
+            "|..#....|",
+            "|.####..|",
+            "|....##.|",
+            "|....##.|",
+            "|....#..|",
+            "|..#.#..|",
+            "|..#.#..|",
+            "|#####..|",
+            "|..###..|",
+            "|...#...|",
+            "|..####.|",
+            "+-------+",
+            "",
+        ];
+        assert_eq!(simulation.chamber.render(None), expected.join("\r\n"));
+    }
+
+    #[test]
+    fn verify_surface_profile() {
+        // The middle of row 1 is sealed off by row 2, so only the gap on the left of row 2 is reachable
+        let config = ChamberConfig::default();
+        let mut chamber = Chamber::new(&config);
+        chamber.mark_formation_in_row(0, 0b1111111);
+        chamber.mark_formation_in_row(1, 0b1000001);
+        chamber.mark_formation_in_row(2, 0b1111110);
+        let profile = chamber.find_surface_profile();
+        assert_eq!(profile, vec![0b0000001]);
+
+        chamber.drop_unreachable_rows(&profile);
+        assert_eq!(chamber.formations.len(), 1);
+        assert_eq!(chamber.get_row(1), u8::MAX);
+        assert_eq!(chamber.get_row(2), 0b1111110);
+        assert_eq!(chamber.render(None), "|.######|\r\n");
+    }
+}
@@ -1,257 +1,36 @@
-use std::collections::{VecDeque, HashMap};
-use grid::Grid;
-use itertools::Itertools;
+use crate::util::cycle::{CycleDetector, Strategy};
+use super::lib::{ChamberConfig, Simulation, parse_gas_jets, parse_rocks};
 
-#[derive(Debug)]
-struct Position2D {
-    x: i32,
-    y: i32
-}
-
-impl Position2D {
-    fn move_dir(&self, direction: Direction) -> Position2D {
-        match direction {
-            Direction::Left => Position2D { x: self.x - 1, y: self.y },
-            Direction::Right => Position2D { x: self.x + 1, y: self.y },
-            Direction::Down => Position2D { x: self.x, y: self.y - 1 },
-        }
-    }
-}
-
-#[derive(Clone)]
-struct Rock {
-    shape: Grid<bool>
-}
-
-impl Rock {
-    fn get_offset_shape_coordinates<'a>(&'a self, bottom_left_pos: &'a Position2D) -> impl Iterator<Item=Position2D> + 'a {
-        let row_indices = 0..self.shape.rows();
-        let column_indices = 0..self.shape.cols();
-
-        row_indices.cartesian_product(column_indices)
-            .filter(|(y, x)| self.shape[*y][*x])
-            .map(|(y, x)| {
-                // Convert from rock to chamber coordinate
-                let chamber_y = bottom_left_pos.y + (self.shape.rows() - 1 - y) as i32;
-                let chamber_x = bottom_left_pos.x + x as i32;
-                Position2D {x: chamber_x, y: chamber_y}
-            })
-    }
-}
-
-#[derive(Clone, Copy)]
-enum Direction {
-    Left,
-    Right,
-    Down
-}
-
-struct Chamber<const WIDTH: usize> {
-    highest_free_row: i32,
-    lowest_filled_row: i32,
-    column_heights: [i32; WIDTH],
-    formations: VecDeque<[bool; WIDTH]>,
-}
-
-impl<const WIDTH: usize> Chamber<WIDTH> {
-    fn new() -> Chamber<WIDTH> {
-        Chamber {
-            highest_free_row: 0,
-            lowest_filled_row: -1,
-            column_heights: [0; WIDTH],
-            formations: [[false; WIDTH]].into_iter().collect()
-        }
-    }
-
-    fn get_row(&self, y: i32) -> &[bool; WIDTH] {
-        &self.formations[(y - self.lowest_filled_row - 1) as usize]
-    }
-
-    fn get_row_mut(&mut self, y: i32) -> &mut [bool; WIDTH] {
-        &mut self.formations[(y - self.lowest_filled_row - 1) as usize]
-    }
-
-    fn is_formation_at_position(&self, position: &Position2D) -> bool {
-        if position.y >= self.highest_free_row {
-            return false;
-        }
-
-        if position.y <= self.lowest_filled_row {
-            return true;
-        }
-
-        return self.get_row(position.y)[position.x as usize];
-    }
-
-    fn mark_formation_at_position(&mut self, position: &Position2D) {
-        if position.y <= self.lowest_filled_row {
-            // We assume everything below a certain point is filled anyway
-            return;
-        }
-
-        // Check if we have enough space on top
-        if position.y >= self.highest_free_row as i32 {
-            // We must first allocate more room in the chamber
-            let rows_to_add = position.y + 1 - self.highest_free_row;
-            self.highest_free_row = position.y + 1;
-            for _ in 0..rows_to_add {
-                self.formations.push_back([false; WIDTH])
-
-            }
-        }
-
-        // Mark formation
-        self.get_row_mut(position.y)[position.x as usize] = true;
-
-        // Update column heights
-        let column_height = &mut self.column_heights[position.x as usize];
-         *column_height = *column_height.max(&mut position.y.clone());
-
-        // Check if we can drop the bottom to save memory
-        let extra_rows_to_keep = 10; // Adjust as necessary to ensure hashing works for the input
-        let new_lowest_filled_row = *self.column_heights.iter().min().unwrap() - extra_rows_to_keep;
-        let rows_to_drop = new_lowest_filled_row - self.lowest_filled_row;
-        if rows_to_drop > 0 {
-            for _ in 0..rows_to_drop {
-                self.formations.pop_front();
-            }
-            self.lowest_filled_row = new_lowest_filled_row;
-        }
-    }  
-
-    fn rock_fits_at(&self, rock: &Rock, bottom_left_pos: &Position2D) -> bool {
-        // Is rock outside the bounds of the chamber?
-        if bottom_left_pos.x < 0 { return false; }    // Too far left
-        if bottom_left_pos.y < 0 { return false; }    // Too far down 
-        let right_pos =bottom_left_pos.x + (rock.shape.cols() as i32 - 1);
-        if right_pos >= WIDTH as i32 { return false; } // Too far right
-
-        // Is rock above all existing formations?
-        if bottom_left_pos.y >= self.highest_free_row {
-            return true
-        }
-
-        // Is rock obstructed by an existing settled formation?
-        for chamber_pos in rock.get_offset_shape_coordinates(bottom_left_pos) {
-            if self.is_formation_at_position(&chamber_pos) {
-                // This part of the rock overlaps with an existing formation
-                return false;
-            }
-        }
-
-        true
-    }
-
-    fn stamp_formation(&mut self, rock: &Rock, bottom_left_pos: &Position2D) {
-        for chamber_pos in rock.get_offset_shape_coordinates(bottom_left_pos) {
-            self.mark_formation_at_position(&chamber_pos);
-        }
-    }
-
-    fn simulate_rock(&mut self, rock: &Rock, gas_jets: &mut impl Iterator<Item = Direction>) {
-        let mut bottom_left_pos = Position2D {x: 2, y: self.highest_free_row + 3};
-        loop {
-            // Push with gas (if it can move in that direction)
-            let gas_dir = gas_jets.next().unwrap();
-            let candidate_pos = bottom_left_pos.move_dir(gas_dir);
-
-            if self.rock_fits_at(rock, &candidate_pos) {
-                bottom_left_pos = candidate_pos;
-            }
-
-            // Attempt to drop the rock
-            let candidate_pos = bottom_left_pos.move_dir(Direction::Down);
-            if self.rock_fits_at(rock, &candidate_pos) {
-                // Keep simulating after drop
-                bottom_left_pos = candidate_pos;
-                continue;
-            } else {
-                // We can't move down any further
-                self.stamp_formation(rock, &bottom_left_pos);
-                return;
-            }
-
-        }
-    }
-
-    // fn print(&self) {
-    //     for y in (0..self.formations.len()).rev() {
-    //         print!("{}: |", y);
-    //         for x in self.formations[y] {
-    //             if x {
-    //                 print!("#")
-    //             } else {
-    //                 print!(".")
-    //             }
-    //         }
-    //         println!("|")
-    //     }
-    //     println!("+-------+")
-    // }
-
-}
-
-fn get_infinite_rocks() -> impl Iterator<Item=Rock> {
-    let shapes = [
-        Grid::<bool>::from_vec([true, true, true, true].to_vec(), 4), // Line
-        Grid::<bool>::from_vec([false, true, false,
-                                 true, true, true,
-                                false, true, false].to_vec(), 3), // Plus
-        Grid::<bool>::from_vec([false, false, true,
-                                false, false, true,
-                                 true,  true, true].to_vec(), 3), // Corner
-        Grid::<bool>::from_vec([true, true, true, true].to_vec(), 1), // Pipe
-        Grid::<bool>::from_vec([true, true, true, true].to_vec(), 2), // Quad
-    ];
-
-    shapes.into_iter().map(|shape| Rock {shape}).cycle()
+pub fn solve(input: &str, num_rocks: usize) -> usize {
+    solve_with_strategy(input, num_rocks, Strategy::HashMap)
 }
 
-fn parse_gas_jets(input: &str) -> Vec<Direction> {
-    return input.chars().map(|c| match c {
-        '<' => Direction::Left,
-        '>' => Direction::Right,
-        _ => unreachable!()
-    }).collect_vec();
+pub fn solve_with_strategy(input: &str, num_rocks: usize, strategy: Strategy) -> usize {
+    solve_with(input, num_rocks, super::ROCKS, &ChamberConfig::default(), strategy)
 }
 
-pub fn solve(input: &str, num_rocks: usize) -> usize {
-    let gas_jets = parse_gas_jets(input);
-    let mut infinite_gas_jets = gas_jets.into_iter().cycle();
-    let mut chamber: Chamber<7> = Chamber::new();
-
-    // Initialize cache
-    let mut height_after_rock = Vec::new();
-    let mut seen_states = HashMap::new();
-
-    for (i, rock) in get_infinite_rocks().take(num_rocks).enumerate() {
-        chamber.simulate_rock(&rock, &mut infinite_gas_jets);
-
-        height_after_rock.push(chamber.highest_free_row as usize);
-        let seen = seen_states.insert(chamber.formations.clone(), i);
+pub fn solve_with(input: &str, num_rocks: usize, rocks: &str, config: &ChamberConfig, strategy: Strategy) -> usize {
+    let mut simulation = Simulation::new(parse_rocks(rocks), parse_gas_jets(input), config);
+    let mut cycle_detector = CycleDetector::new(strategy);
 
-        if let Some(cycle_start_i) = seen {
-            // We're starting to repeat and we found the cycle point
-            
-            let cycle_period = i - cycle_start_i;
-            println!("Input cycles starting from rock {} with period {}", cycle_start_i, cycle_period);
-            
-            let remaining_rocks = num_rocks - (i + 1);
-            let current_height = height_after_rock[i];
+    for _ in 0..num_rocks {
+        simulation.drop_rock();
 
-            let remaining_full_cycles = remaining_rocks / cycle_period;
-            let height_increase_per_full_cycle = height_after_rock[i] - height_after_rock[cycle_start_i];
-            let height_from_remaining_full_cycles = remaining_full_cycles * height_increase_per_full_cycle;
+        // Everything that affects the next rocks: which rock and jet come next, and the shape of
+        // the surface they can fall onto
+        let profile = simulation.chamber.find_surface_profile();
+        simulation.chamber.drop_unreachable_rows(&profile);
+        let state = (simulation.get_rock_index(), simulation.get_jet_index(), profile);
 
-            let rocks_in_remainder_cycle = remaining_rocks % cycle_period;
-            let height_from_remainder_cycle = height_after_rock[cycle_start_i + rocks_in_remainder_cycle] - height_after_rock[cycle_start_i];
-            
-            return current_height + height_from_remaining_full_cycles + height_from_remainder_cycle;
+        let height = simulation.chamber.get_height() as usize;
+        if cycle_detector.record(state, height).is_some() {
+            // We're starting to repeat, so we can skip ahead to the final rock
+            return cycle_detector.extrapolate(num_rocks - 1).unwrap();
         }
     }
 
     // No cycles found in input
-    chamber.highest_free_row as usize
+    simulation.chamber.get_height() as usize
 }
 
 pub mod tests {
@@ -278,11 +57,53 @@ pub mod tests {
         assert_eq!(super::solve(super::super::INPUT, 1_000_000_000_000), 1_572_093_023_267);
     }
 
+    // Drop every rock without skipping ahead, to check the cycle detection against
+    #[cfg(test)]
+    fn simulate(input: &str, num_rocks: usize, rocks: &str, config: &super::ChamberConfig) -> usize {
+        use super::super::lib::{Simulation, parse_gas_jets, parse_rocks};
+        let mut simulation = Simulation::new(parse_rocks(rocks), parse_gas_jets(input), config);
+        for _ in 0..num_rocks {
+            simulation.drop_rock();
+        }
+        simulation.chamber.get_height() as usize
+    }
+
+    #[test]
+    fn verify_synthetic_cases() {
+        use super::{ChamberConfig, solve_with};
+        use crate::util::cycle::Strategy;
+
+        // A single column of single squares just stacks up
+        let column = ChamberConfig { width: 1, spawn_offset_x: 0, spawn_offset_y: 3 };
+        assert_eq!(solve_with("<", 1_000_000_000_000, "#", &column, Strategy::HashMap), 1_000_000_000_000);
+
+        let cases = [
+            ("<<>", "##\n\n#\n#", ChamberConfig { width: 4, spawn_offset_x: 0, spawn_offset_y: 1 }),
+            ("><<>>>", ".#.\n###\n.#.\n\n##\n##", ChamberConfig { width: 5, spawn_offset_x: 1, spawn_offset_y: 2 }),
+            (super::super::INPUT_EXAMPLE, "###\n\n..#\n..#\n###\n\n#\n#", ChamberConfig { width: 6, spawn_offset_x: 2, spawn_offset_y: 3 }),
+            (super::super::INPUT_EXAMPLE, super::super::ROCKS, ChamberConfig { width: 8, spawn_offset_x: 3, spawn_offset_y: 4 }),
+        ];
+        for (jets, rocks, config) in &cases {
+            for num_rocks in [1, 100, 2022, 5000] {
+                let expected = simulate(jets, num_rocks, rocks, config);
+                assert_eq!(solve_with(jets, num_rocks, rocks, config, Strategy::HashMap), expected);
+                assert_eq!(solve_with(jets, num_rocks, rocks, config, Strategy::Brent), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn verify_solution_brent() {
+        let strategy = crate::util::cycle::Strategy::Brent;
+        assert_eq!(super::solve_with_strategy(super::super::INPUT, 1_000_000_000_000, strategy), 1_572_093_023_267);
+    }
+
     pub fn benchmark(c: &mut Criterion) {
         let path = Path::new(file!());
         let day = path.parent().unwrap().to_str().unwrap();
         let solution = path.file_stem().unwrap().to_str().unwrap();
         let id = day.to_owned() + "_" + solution; // dayX_partY_solveZ
         c.bench_function( &id, |b| b.iter(|| super::solve(super::super::INPUT, 1_000_000_000_000)));
+        c.bench_function( &(id + "_2022"), |b| b.iter(|| super::solve(super::super::INPUT, 2022)));
     }
 }
\ No newline at end of file
@@ -0,0 +1,536 @@
+use std::collections::{HashSet, VecDeque};
+use itertools::Itertools;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Position3D {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32
+}
+
+impl Position3D {
+    pub fn neighbors(&self) -> [Position3D; 6] {
+        [
+            Position3D {x: self.x + 1, y: self.y,     z: self.z },
+            Position3D {x: self.x - 1, y: self.y,     z: self.z },
+            Position3D {x: self.x,     y: self.y + 1, z: self.z },
+            Position3D {x: self.x,     y: self.y - 1, z: self.z },
+            Position3D {x: self.x,     y: self.y,     z: self.z + 1 },
+            Position3D {x: self.x,     y: self.y,     z: self.z - 1 },
+        ]
+    }
+
+    // Every position sharing a face, edge or corner with this one
+    fn neighbors_26(&self) -> impl Iterator<Item=Position3D> {
+        let Position3D { x, y, z } = *self;
+        (-1..=1)
+            .cartesian_product(-1..=1)
+            .cartesian_product(-1..=1)
+            .filter(|&((dx, dy), dz)| (dx, dy, dz) != (0, 0, 0))
+            .map(move |((dx, dy), dz)| Position3D { x: x + dx, y: y + dy, z: z + dz })
+    }
+}
+
+// The face of a voxel, facing towards the neighbor in the direction of the normal
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Face {
+    pub voxel: Position3D,
+    pub normal: Position3D // One of the 6 unit directions
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Bounds {
+    pub min: Position3D,
+    pub max: Position3D // Inclusive
+}
+
+impl Bounds {
+    pub fn contains(&self, pos: &Position3D) -> bool {
+        (self.min.x..=self.max.x).contains(&pos.x) &&
+            (self.min.y..=self.max.y).contains(&pos.y) &&
+            (self.min.z..=self.max.z).contains(&pos.z)
+    }
+
+    pub fn expand(&self, margin: i32) -> Bounds {
+        Bounds {
+            min: Position3D { x: self.min.x - margin, y: self.min.y - margin, z: self.min.z - margin },
+            max: Position3D { x: self.max.x + margin, y: self.max.y + margin, z: self.max.z + margin },
+        }
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item=Position3D> + '_ {
+        (self.min.z..=self.max.z)
+            .cartesian_product(self.min.y..=self.max.y)
+            .cartesian_product(self.min.x..=self.max.x)
+            .map(|((z, y), x)| Position3D { x, y, z })
+    }
+
+    // Number of positions in the box, saturating rather than overflowing for huge boxes
+    pub fn volume(&self) -> usize {
+        [(self.min.x, self.max.x), (self.min.y, self.max.y), (self.min.z, self.max.z)]
+            .iter()
+            .map(|&(min, max)| min.abs_diff(max) as usize + 1)
+            .fold(1, usize::saturating_mul)
+    }
+}
+
+// A dense grid of flags covering a box, which is much faster to look up than hashing
+struct BoxGrid {
+    bounds: Bounds,
+    cells: Vec<bool>
+}
+
+impl BoxGrid {
+    fn new(bounds: Bounds) -> BoxGrid {
+        BoxGrid { bounds, cells: vec![false; bounds.volume()] }
+    }
+
+    fn index(&self, pos: &Position3D) -> Option<usize> {
+        if !self.bounds.contains(pos) {
+            return None;
+        }
+        let x_size = (self.bounds.max.x - self.bounds.min.x + 1) as usize;
+        let y_size = (self.bounds.max.y - self.bounds.min.y + 1) as usize;
+        let (x, y, z) = (pos.x - self.bounds.min.x, pos.y - self.bounds.min.y, pos.z - self.bounds.min.z);
+        Some((z as usize * y_size + y as usize) * x_size + x as usize)
+    }
+
+    fn get(&self, pos: &Position3D) -> bool {
+        self.index(pos).is_some_and(|i| self.cells[i])
+    }
+
+    // Returns whether the flag was set before, like `HashSet::remove`
+    fn unset(&mut self, pos: &Position3D) -> bool {
+        self.index(pos).is_some_and(|i| std::mem::replace(&mut self.cells[i], false))
+    }
+
+    // Returns whether the flag was newly set, like `HashSet::insert`
+    fn set(&mut self, pos: &Position3D) -> bool {
+        let i = self.index(pos).unwrap();
+        !std::mem::replace(&mut self.cells[i], true)
+    }
+}
+
+// Boxes up to this many cells are checked with a `BoxGrid` rather than by hashing
+const MAX_DENSE_VOLUME: usize = 1 << 22;
+
+// The air around a group of voxels that touch each other by at least a corner
+struct GroupAir {
+    first_voxel: Position3D,
+    exterior_faces: Vec<Face>, // Faces reached from outside the group, ignoring all other voxels
+    pockets: Vec<VoxelSet>     // Air enclosed by the group, ignoring all other voxels
+}
+
+// A sparse set of unit cubes, which can be anywhere (including negative coordinates)
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct VoxelSet {
+    voxels: HashSet<Position3D>
+}
+
+// A connected region of air that is completely enclosed by lava
+#[derive(Clone, Debug)]
+pub struct Pocket {
+    pub voxels: VoxelSet,
+    pub volume: usize,
+    pub surface_area: usize
+}
+
+impl VoxelSet {
+    pub fn new() -> VoxelSet {
+        VoxelSet::default()
+    }
+
+    pub fn insert(&mut self, pos: Position3D) -> bool {
+        self.voxels.insert(pos)
+    }
+
+    pub fn contains(&self, pos: &Position3D) -> bool {
+        self.voxels.contains(pos)
+    }
+
+    pub fn len(&self) -> usize {
+        self.voxels.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.voxels.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item=&Position3D> {
+        self.voxels.iter()
+    }
+
+    // Smallest box containing every voxel, or None if the set is empty
+    pub fn bounds(&self) -> Option<Bounds> {
+        let (x_min, x_max) = self.voxels.iter().map(|v| v.x).minmax().into_option()?;
+        let (y_min, y_max) = self.voxels.iter().map(|v| v.y).minmax().into_option()?;
+        let (z_min, z_max) = self.voxels.iter().map(|v| v.z).minmax().into_option()?;
+        Some(Bounds {
+            min: Position3D { x: x_min, y: y_min, z: z_min },
+            max: Position3D { x: x_max, y: y_max, z: z_max },
+        })
+    }
+
+    // Number of faces which aren't touching another voxel in the set
+    pub fn surface_area(&self) -> usize {
+        let neighbors = self.voxels.iter().flat_map(|voxel| voxel.neighbors());
+        match self.bounds() {
+            Some(bounds) if bounds.volume() <= MAX_DENSE_VOLUME => {
+                let grid = self.to_grid(bounds);
+                neighbors.filter(|neighbor| !grid.get(neighbor)).count()
+            },
+            _ => neighbors.filter(|neighbor| !self.contains(neighbor)).count()
+        }
+    }
+
+    fn to_grid(&self, bounds: Bounds) -> BoxGrid {
+        let mut grid = BoxGrid::new(bounds);
+        for voxel in &self.voxels {
+            grid.set(voxel);
+        }
+        grid
+    }
+
+    // Split into groups of voxels that are connected by their faces
+    pub fn connected_components(&self) -> Vec<VoxelSet> {
+        self.components_by(|voxel| voxel.neighbors())
+    }
+
+    fn components_by<I: IntoIterator<Item=Position3D>>(&self, neighbors: impl Fn(&Position3D) -> I) -> Vec<VoxelSet> {
+        match self.bounds() {
+            Some(bounds) if bounds.volume() <= MAX_DENSE_VOLUME => {
+                let mut unvisited = self.to_grid(bounds);
+                self.components_with(neighbors, |pos| unvisited.unset(pos))
+            },
+            _ => {
+                let mut unvisited = self.voxels.clone();
+                self.components_with(neighbors, |pos| unvisited.remove(pos))
+            }
+        }
+    }
+
+    // `visit` marks a voxel as visited, returning false if it isn't in the set or was already visited
+    fn components_with<I: IntoIterator<Item=Position3D>>(
+        &self,
+        neighbors: impl Fn(&Position3D) -> I,
+        mut visit: impl FnMut(&Position3D) -> bool
+    ) -> Vec<VoxelSet> {
+        let mut components = Vec::new();
+        // Visit in a fixed order so the components come out in the same order each time
+        for start in self.voxels.iter().sorted() {
+            if !visit(start) {
+                continue;
+            }
+
+            let mut component = VoxelSet::new();
+            let mut queue = VecDeque::from([*start]);
+            while let Some(voxel) = queue.pop_front() {
+                component.insert(voxel);
+                for neighbor in neighbors(&voxel) {
+                    if visit(&neighbor) {
+                        queue.push_back(neighbor);
+                    }
+                }
+            }
+            components.push(component);
+        }
+        components
+    }
+
+    // Flood fill around a group of voxels within its own bounds (plus a margin of 1 so the outside air is
+    // connected), so that droplets far apart from each other don't need one huge box
+    fn find_group_air(group: &VoxelSet) -> GroupAir {
+        let bounds = group.bounds().unwrap().expand(1);
+        if bounds.volume() <= MAX_DENSE_VOLUME {
+            VoxelSet::find_group_air_dense(group, bounds)
+        } else {
+            VoxelSet::find_group_air_sparse(group)
+        }
+    }
+
+    fn find_group_air_dense(group: &VoxelSet, bounds: Bounds) -> GroupAir {
+        let lava = group.to_grid(bounds);
+        let mut visited = BoxGrid::new(bounds);
+        // Start at a point in the margin
+        group.fill_air(
+            bounds.min,
+            |pos| bounds.contains(pos),
+            |pos| lava.get(pos),
+            |pos| visited.set(pos),
+            bounds.positions())
+    }
+
+    // A group spread out along a diagonal can have a huge bounding box while touching very little of it.
+    // Every exterior face can be reached by moving through air that touches the group by at least a corner,
+    // so the flood fill from outside only needs to explore that thin shell rather than the whole box.
+    fn find_group_air_sparse(group: &VoxelSet) -> GroupAir {
+        let shell: HashSet<Position3D> = group
+            .iter()
+            .flat_map(Position3D::neighbors_26)
+            .filter(|pos| !group.contains(pos))
+            .collect();
+        let mut visited = HashSet::new();
+        // Nothing in the group is further left than its smallest voxel, so the air beside it is outside
+        let first = group.iter().min().unwrap();
+        group.fill_air(
+            Position3D { x: first.x - 1, ..*first },
+            |pos| shell.contains(pos),
+            |pos| group.contains(pos),
+            |pos| visited.insert(*pos),
+            shell.iter().sorted().copied())
+    }
+
+    // Flood fill from `start` through the positions where `in_fill` is true to find the exterior faces, then
+    // split the air that wasn't reached into pockets by looking at each of `pocket_starts`.
+    // `visit` marks a position as visited, returning false if it already was.
+    fn fill_air(
+        &self,
+        start: Position3D,
+        in_fill: impl Fn(&Position3D) -> bool,
+        is_lava: impl Fn(&Position3D) -> bool,
+        mut visit: impl FnMut(&Position3D) -> bool,
+        pocket_starts: impl Iterator<Item=Position3D>
+    ) -> GroupAir {
+        // Flood fill from outside to find exterior surfaces
+        let mut exterior_faces = Vec::new();
+        visit(&start);
+        let mut queue = VecDeque::from([start]);
+        while let Some(pos) = queue.pop_front() {
+            for neighbor in pos.neighbors() {
+                if is_lava(&neighbor) {
+                    // Found an exterior face
+                    let normal = Position3D { x: pos.x - neighbor.x, y: pos.y - neighbor.y, z: pos.z - neighbor.z };
+                    exterior_faces.push(Face { voxel: neighbor, normal });
+                } else if in_fill(&neighbor) && visit(&neighbor) {
+                    // Found another position to explore
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+
+        // Any air the flood fill didn't reach is trapped, so split it into separate pockets
+        let mut pockets = Vec::new();
+        for start in pocket_starts {
+            if is_lava(&start) || !visit(&start) {
+                continue;
+            }
+            let mut pocket = VoxelSet::new();
+            let mut queue = VecDeque::from([start]);
+            while let Some(pos) = queue.pop_front() {
+                pocket.insert(pos);
+                for neighbor in pos.neighbors() {
+                    if !is_lava(&neighbor) && visit(&neighbor) {
+                        queue.push_back(neighbor);
+                    }
+                }
+            }
+            pockets.push(pocket);
+        }
+
+        GroupAir { first_voxel: *self.iter().min().unwrap(), exterior_faces, pockets }
+    }
+
+    // Air can't squeeze between voxels that only touch by an edge or corner, so each region of air is
+    // surrounded by a single group of voxels connected that way. We can look at the air around each group
+    // separately, and then account for any groups that sit inside another group's pockets.
+    fn find_air_by_group(&self) -> Vec<GroupAir> {
+        self.components_by(Position3D::neighbors_26)
+            .iter()
+            .map(VoxelSet::find_group_air)
+            .collect()
+    }
+
+    // Find the regions of air that are completely enclosed by the voxels
+    pub fn find_pockets(&self) -> Vec<Pocket> {
+        let groups = self.find_air_by_group();
+        let mut pockets = Vec::new();
+        for (i, group) in groups.iter().enumerate() {
+            for pocket in &group.pockets {
+                // Other groups inside this pocket take up some of its space, and own the pockets inside them
+                let nested = groups
+                    .iter()
+                    .enumerate()
+                    .filter(|&(j, other)| j != i && pocket.contains(&other.first_voxel))
+                    .flat_map(|(_, other)| other.pockets.iter())
+                    .collect_vec();
+                let voxels: VoxelSet = pocket
+                    .iter()
+                    .filter(|pos| !self.contains(pos) && !nested.iter().any(|inner| inner.contains(pos)))
+                    .copied()
+                    .collect();
+                pockets.push(Pocket { volume: voxels.len(), surface_area: voxels.surface_area(), voxels });
+            }
+        }
+        pockets
+    }
+
+    // Faces that can be reached from outside
+    pub fn exterior_faces(&self) -> Vec<Face> {
+        let groups = self.find_air_by_group();
+
+        // Groups sitting inside another group's pocket can't be reached from outside at all
+        let is_nested = |group: &GroupAir| groups
+            .iter()
+            .flat_map(|other| other.pockets.iter())
+            .any(|pocket| pocket.contains(&group.first_voxel));
+
+        groups
+            .iter()
+            .filter(|group| !is_nested(group))
+            .flat_map(|group| group.exterior_faces.iter().copied())
+            .collect()
+    }
+
+    // Number of faces that can be reached from outside
+    pub fn exterior_surface_area(&self) -> usize {
+        self.exterior_faces().len()
+    }
+
+    // Faces that look into any of the air pockets
+    pub fn interior_faces(&self) -> Vec<Face> {
+        self.find_pockets()
+            .iter()
+            .flat_map(|pocket| pocket.voxels.iter())
+            .flat_map(|air| air.neighbors().into_iter().map(move |voxel| Face {
+                voxel,
+                normal: Position3D { x: air.x - voxel.x, y: air.y - voxel.y, z: air.z - voxel.z }
+            }))
+            .filter(|face| self.contains(&face.voxel))
+            .collect_vec()
+    }
+}
+
+impl FromIterator<Position3D> for VoxelSet {
+    fn from_iter<I: IntoIterator<Item=Position3D>>(iter: I) -> Self {
+        VoxelSet { voxels: iter.into_iter().collect() }
+    }
+}
+
+pub fn parse_voxels(input: &str) -> VoxelSet {
+    input
+        .lines()
+        .map(|coord| {
+            let mut components = coord.split(',');
+            Position3D {
+                x: components.next().unwrap().parse().unwrap(),
+                y: components.next().unwrap().parse().unwrap(),
+                z: components.next().unwrap().parse().unwrap()
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+    use super::{Bounds, GroupAir, Position3D, VoxelSet, parse_voxels};
+
+    #[test]
+    fn verify_pockets() {
+        // Each example has a single trapped cube of air
+        for input in [super::super::INPUT_EXAMPLE, super::super::INPUT_EXAMPLE_2] {
+            let lava = parse_voxels(input);
+            let pockets = lava.find_pockets();
+            assert_eq!(pockets.len(), 1);
+            assert_eq!((pockets[0].volume, pockets[0].surface_area), (1, 6));
+        }
+
+        let pocket = &parse_voxels(super::super::INPUT_EXAMPLE).find_pockets()[0];
+        assert!(pocket.voxels.contains(&Position3D { x: 2, y: 2, z: 5 }));
+    }
+
+    #[test]
+    fn verify_surface_areas_add_up() {
+        // Every face is either on the outside or facing into a pocket
+        let lava = parse_voxels(super::super::INPUT);
+        let pockets = lava.find_pockets();
+        let interior_area = pockets.iter().map(|pocket| pocket.surface_area).sum::<usize>();
+        assert_eq!(lava.surface_area(), 4460);
+        assert_eq!(lava.exterior_surface_area(), 2498);
+        assert_eq!(lava.surface_area(), lava.exterior_surface_area() + interior_area);
+    }
+
+    #[test]
+    fn verify_negative_coordinates() {
+        // Moving the droplet doesn't change anything
+        let lava = parse_voxels(super::super::INPUT_EXAMPLE);
+        let moved: VoxelSet = lava.iter().map(|pos| Position3D { x: pos.x - 10, y: -pos.y, z: pos.z - 3 }).collect();
+        assert_eq!(moved.surface_area(), 64);
+        assert_eq!(moved.exterior_surface_area(), 58);
+        let pockets = moved.find_pockets();
+        assert_eq!(pockets.len(), 1);
+        assert!(pockets[0].voxels.contains(&Position3D { x: -8, y: -2, z: 2 }));
+    }
+
+    #[test]
+    fn verify_connected_components() {
+        // Two separate droplets, one of which is a pair of cubes
+        let lava: VoxelSet = [(0, 0, 0), (0, 0, 1), (5, -5, 5), (1, 1, 0)]
+            .into_iter()
+            .map(|(x, y, z)| Position3D { x, y, z })
+            .collect();
+        let sizes = lava.connected_components().iter().map(|component| component.len()).collect_vec();
+        assert_eq!(sizes, vec![2, 1, 1]);
+        assert_eq!(parse_voxels(super::super::INPUT_EXAMPLE_2).connected_components().len(), 1);
+    }
+
+    #[test]
+    fn verify_far_apart() {
+        let lava: VoxelSet = [(0, 0, 0), (1_000_000, -1_000_000, 1_000_000)]
+            .into_iter()
+            .map(|(x, y, z)| Position3D { x, y, z })
+            .collect();
+        assert_eq!(lava.surface_area(), 12);
+        assert_eq!(lava.exterior_surface_area(), 12);
+        assert!(lava.find_pockets().is_empty());
+    }
+
+    #[test]
+    fn verify_diagonal_chain() {
+        // The cubes only touch by their corners, so they form a single group with a huge bounding box
+        let lava: VoxelSet = (0..2000).map(|i| Position3D { x: i, y: i, z: i }).collect();
+        assert_eq!(lava.surface_area(), 2000 * 6);
+        assert_eq!(lava.exterior_surface_area(), 2000 * 6);
+        assert!(lava.find_pockets().is_empty());
+    }
+
+    #[test]
+    fn verify_sparse_air_matches_dense() {
+        let pocket_sets = |group_air: &GroupAir| group_air.pockets
+            .iter()
+            .map(|pocket| pocket.iter().copied().sorted().collect_vec())
+            .sorted()
+            .collect_vec();
+        for group in parse_voxels(super::super::INPUT).components_by(Position3D::neighbors_26) {
+            let dense = VoxelSet::find_group_air_dense(&group, group.bounds().unwrap().expand(1));
+            let sparse = VoxelSet::find_group_air_sparse(&group);
+            assert_eq!(dense.first_voxel, sparse.first_voxel);
+            let face_keys = |group_air: &GroupAir| group_air.exterior_faces
+                .iter()
+                .map(|face| (face.voxel, face.normal))
+                .sorted()
+                .collect_vec();
+            assert_eq!(face_keys(&dense), face_keys(&sparse));
+            assert_eq!(pocket_sets(&dense), pocket_sets(&sparse));
+        }
+    }
+
+    #[test]
+    fn verify_nested_droplet() {
+        // A hollow 5x5x5 cube with a single cube floating in the middle of the space inside
+        let outer = Bounds { min: Position3D { x: 0, y: 0, z: 0 }, max: Position3D { x: 4, y: 4, z: 4 } };
+        let inner = Bounds { min: Position3D { x: 1, y: 1, z: 1 }, max: Position3D { x: 3, y: 3, z: 3 } };
+        let center = Position3D { x: 2, y: 2, z: 2 };
+        let lava: VoxelSet = outer.positions()
+            .filter(|pos| !inner.contains(pos) || *pos == center)
+            .collect();
+
+        assert_eq!(lava.surface_area(), 150 + 54 + 6);
+        assert_eq!(lava.exterior_surface_area(), 150);
+        let pockets = lava.find_pockets();
+        assert_eq!(pockets.len(), 1);
+        assert_eq!((pockets[0].volume, pockets[0].surface_area), (26, 54 + 6));
+        assert!(!pockets[0].voxels.contains(&center));
+        assert_eq!(lava.interior_faces().len(), 54 + 6);
+    }
+}
@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+use super::lib::{Face, Position3D, VoxelSet};
+
+// Corners of a face, counter-clockwise when looking at it from the direction of its normal
+pub fn face_corners(face: &Face) -> [Position3D; 4] {
+    let Face { voxel, normal } = face;
+    let v = [voxel.x, voxel.y, voxel.z];
+    let n = [normal.x, normal.y, normal.z];
+    let axis = n.iter().position(|&d| d != 0).unwrap();
+
+    // The other two axes, in the order that makes their cross product point along the axis
+    let (u, w) = ((axis + 1) % 3, (axis + 2) % 3);
+    let offsets = if n[axis] > 0 {
+        [(0, 0), (1, 0), (1, 1), (0, 1)]
+    } else {
+        [(0, 0), (0, 1), (1, 1), (1, 0)]
+    };
+
+    offsets.map(|(du, dw)| {
+        let mut corner = v;
+        if n[axis] > 0 {
+            corner[axis] += 1; // The face on the far side of the voxel
+        }
+        corner[u] += du;
+        corner[w] += dw;
+        Position3D { x: corner[0], y: corner[1], z: corner[2] }
+    })
+}
+
+fn surfaces(lava: &VoxelSet, include_pockets: bool) -> Vec<(&'static str, Vec<Face>)> {
+    let mut surfaces = vec![("exterior", lava.exterior_faces())];
+    if include_pockets {
+        surfaces.push(("pockets", lava.interior_faces()));
+    }
+    surfaces
+}
+
+// Export the surface as a Wavefront OBJ mesh of square faces, with the exterior and pocket
+// surfaces as separate groups
+pub fn to_obj(lava: &VoxelSet, include_pockets: bool) -> String {
+    let mut vertices = HashMap::new();
+    let mut vertex_lines = String::new();
+    let mut face_lines = String::new();
+    for (name, faces) in surfaces(lava, include_pockets) {
+        writeln!(face_lines, "g {}", name).unwrap();
+        for face in faces {
+            let indices = face_corners(&face).map(|corner| {
+                // Vertices are shared between faces, and OBJ counts them from 1
+                let next_index = vertices.len() + 1;
+                *vertices.entry(corner).or_insert_with(|| {
+                    writeln!(vertex_lines, "v {} {} {}", corner.x, corner.y, corner.z).unwrap();
+                    next_index
+                })
+            });
+            writeln!(face_lines, "f {} {} {} {}", indices[0], indices[1], indices[2], indices[3]).unwrap();
+        }
+    }
+    vertex_lines + &face_lines
+}
+
+// Export the surface as an ASCII STL mesh with two triangles per face, with the exterior and
+// pocket surfaces as separate solids
+pub fn to_stl(lava: &VoxelSet, include_pockets: bool) -> String {
+    let mut output = String::new();
+    for (name, faces) in surfaces(lava, include_pockets) {
+        writeln!(output, "solid {}", name).unwrap();
+        for face in faces {
+            let corners = face_corners(&face);
+            for triangle in [[corners[0], corners[1], corners[2]], [corners[0], corners[2], corners[3]]] {
+                let normal = face.normal;
+                writeln!(output, "  facet normal {} {} {}", normal.x, normal.y, normal.z).unwrap();
+                writeln!(output, "    outer loop").unwrap();
+                for vertex in triangle {
+                    writeln!(output, "      vertex {} {} {}", vertex.x, vertex.y, vertex.z).unwrap();
+                }
+                writeln!(output, "    endloop").unwrap();
+                writeln!(output, "  endfacet").unwrap();
+            }
+        }
+        writeln!(output, "endsolid {}", name).unwrap();
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::lib::{Face, Position3D, VoxelSet, parse_voxels};
+
+    #[test]
+    fn verify_winding() {
+        // The corners go counter-clockwise around the normal, so the cross product of two edges points the same way
+        let origin = Position3D { x: 0, y: 0, z: 0 };
+        for normal in origin.neighbors() {
+            let corners = super::face_corners(&Face { voxel: origin, normal });
+            let edge = |from: Position3D, to: Position3D| [to.x - from.x, to.y - from.y, to.z - from.z];
+            let (a, b) = (edge(corners[0], corners[1]), edge(corners[1], corners[2]));
+            let cross = [a[1] * b[2] - a[2] * b[1], a[2] * b[0] - a[0] * b[2], a[0] * b[1] - a[1] * b[0]];
+            assert_eq!(cross, [normal.x, normal.y, normal.z]);
+        }
+    }
+
+    #[test]
+    fn verify_single_cube() {
+        let cube: VoxelSet = [Position3D { x: -1, y: 2, z: 0 }].into_iter().collect();
+        let obj = super::to_obj(&cube, false);
+        assert_eq!(obj.lines().filter(|line| line.starts_with("v ")).count(), 8);
+        assert_eq!(obj.lines().filter(|line| line.starts_with("f ")).count(), 6);
+        assert!(obj.contains("v -1 2 0\n"));
+        assert!(obj.contains("v 0 3 1\n"));
+    }
+
+    #[test]
+    fn verify_example() {
+        let lava = parse_voxels(super::super::INPUT_EXAMPLE);
+        let obj = super::to_obj(&lava, true);
+        let groups = obj.split("g ").skip(1).map(|group| group.lines().count() - 1).collect::<Vec<_>>();
+        assert_eq!(groups, vec![58, 6]); // Faces in each group
+
+        let stl = super::to_stl(&lava, false);
+        assert!(stl.starts_with("solid exterior\n"));
+        assert!(stl.ends_with("endsolid exterior\n"));
+        assert_eq!(stl.matches("facet normal").count(), 58 * 2);
+        assert_eq!(super::to_stl(&lava, true).matches("facet normal").count(), (58 + 6) * 2);
+    }
+
+    #[test]
+    fn verify_far_apart() {
+        // Each cube is flood filled within its own bounds, rather than one box spanning both
+        let cubes: VoxelSet = [Position3D { x: 0, y: 0, z: 0 }, Position3D { x: 1000, y: 1000, z: 1000 }].into_iter().collect();
+        let obj = super::to_obj(&cubes, true);
+        assert_eq!(obj.lines().filter(|line| line.starts_with("v ")).count(), 16);
+        assert_eq!(obj.lines().filter(|line| line.starts_with("f ")).count(), 12);
+        assert!(obj.contains("v 1001 1001 1001\n"));
+        assert_eq!(super::to_stl(&cubes, true).matches("facet normal").count(), 12 * 2);
+    }
+}
@@ -1,3 +1,5 @@
+pub mod lib;
+pub mod mesh;
 pub mod part1;
 pub mod part2;
 
@@ -0,0 +1,226 @@
+use std::str::FromStr;
+
+// Resources are identified by their index in the blueprint's list of resource names,
+// and bots by the resource that they collect
+pub type Resource = usize;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Recipe {
+    pub bot: Resource,
+    pub costs: Vec<(Resource, u16)>
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Blueprint {
+    pub id: u32,
+    pub resources: Vec<String>, // In the order they're first mentioned
+    pub recipes: Vec<Recipe>
+}
+
+impl Blueprint {
+    pub fn find_resource(&self, name: &str) -> Option<Resource> {
+        self.resources.iter().position(|resource| resource == name)
+    }
+
+    pub fn resource_name(&self, resource: Resource) -> &str {
+        &self.resources[resource]
+    }
+
+    // The most of each resource we could ever spend in a single minute, since we can only build one bot at a time
+    pub fn max_spend(&self) -> Vec<u16> {
+        let mut max_spend = vec![0; self.resources.len()];
+        for recipe in &self.recipes {
+            for &(resource, cost) in &recipe.costs {
+                max_spend[resource] = max_spend[resource].max(cost);
+            }
+        }
+        max_spend
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParseBlueprintError {
+    pub position: usize // Byte offset of the unexpected word (or end of input)
+}
+
+// Recursive-descent parser for the grammar:
+//   blueprint := "Blueprint" number ":" recipe+
+//   recipe    := "Each" name "robot" "costs" cost ("and" cost)* "."
+//   cost      := number name
+// Whitespace (including line breaks) is allowed between any two words.
+struct Parser<'a> {
+    bytes: &'a [u8],
+    position: usize,
+    resources: Vec<String>
+}
+
+impl<'a> Parser<'a> {
+    fn skip_whitespace(&mut self) {
+        while self.bytes.get(self.position).is_some_and(|byte| byte.is_ascii_whitespace()) {
+            self.position += 1;
+        }
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        self.skip_whitespace();
+        self.bytes.get(self.position).copied()
+    }
+
+    // The run of bytes matching the predicate at the next word, without consuming it
+    fn peek_run(&mut self, predicate: impl Fn(&u8) -> bool) -> &'a str {
+        self.skip_whitespace();
+        let bytes = self.bytes;
+        let length = bytes[self.position..].iter().take_while(|byte| predicate(byte)).count();
+        std::str::from_utf8(&bytes[self.position..self.position + length]).unwrap()
+    }
+
+    fn error(&self) -> ParseBlueprintError {
+        ParseBlueprintError { position: self.position }
+    }
+
+    fn expect_word(&mut self, word: &str) -> Result<(), ParseBlueprintError> {
+        if self.peek_run(u8::is_ascii_alphabetic) == word {
+            self.position += word.len();
+            Ok(())
+        } else {
+            Err(self.error())
+        }
+    }
+
+    fn expect_byte(&mut self, byte: u8) -> Result<(), ParseBlueprintError> {
+        if self.peek() == Some(byte) {
+            self.position += 1;
+            Ok(())
+        } else {
+            Err(self.error())
+        }
+    }
+
+    fn parse_number<T: FromStr>(&mut self) -> Result<T, ParseBlueprintError> {
+        let digits = self.peek_run(u8::is_ascii_digit);
+        let number = digits.parse().map_err(|_| self.error())?;
+        self.position += digits.len();
+        Ok(number)
+    }
+
+    fn parse_resource(&mut self) -> Result<Resource, ParseBlueprintError> {
+        let name = self.peek_run(u8::is_ascii_alphabetic);
+        if name.is_empty() {
+            return Err(self.error());
+        }
+        self.position += name.len();
+
+        let resource = match self.resources.iter().position(|resource| resource == name) {
+            Some(resource) => resource,
+            None => {
+                self.resources.push(name.to_owned());
+                self.resources.len() - 1
+            }
+        };
+        Ok(resource)
+    }
+
+    fn parse_recipe(&mut self) -> Result<Recipe, ParseBlueprintError> {
+        self.expect_word("Each")?;
+        let bot = self.parse_resource()?;
+        self.expect_word("robot")?;
+        self.expect_word("costs")?;
+
+        let mut costs = Vec::new();
+        loop {
+            let amount = self.parse_number()?;
+            let resource = self.parse_resource()?;
+            costs.push((resource, amount));
+            if self.peek() == Some(b'.') {
+                self.position += 1;
+                return Ok(Recipe { bot, costs });
+            }
+            self.expect_word("and")?;
+        }
+    }
+
+    fn parse_blueprint(&mut self) -> Result<Blueprint, ParseBlueprintError> {
+        self.resources.clear();
+        self.expect_word("Blueprint")?;
+        let id = self.parse_number()?;
+        self.expect_byte(b':')?;
+
+        let mut recipes = vec![self.parse_recipe()?];
+        while self.peek_run(u8::is_ascii_alphabetic) == "Each" {
+            recipes.push(self.parse_recipe()?);
+        }
+        Ok(Blueprint { id, resources: std::mem::take(&mut self.resources), recipes })
+    }
+}
+
+impl FromStr for Blueprint {
+    type Err = ParseBlueprintError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser { bytes: s.as_bytes(), position: 0, resources: Vec::new() };
+        let blueprint = parser.parse_blueprint()?;
+        if parser.peek().is_some() {
+            // Trailing words after the last recipe
+            return Err(parser.error());
+        }
+        Ok(blueprint)
+    }
+}
+
+// Parse every blueprint in the input. Blueprints may be split over several lines.
+pub fn parse_blueprints(input: &str) -> Result<Vec<Blueprint>, ParseBlueprintError> {
+    let mut parser = Parser { bytes: input.as_bytes(), position: 0, resources: Vec::new() };
+    let mut blueprints = Vec::new();
+    while parser.peek().is_some() {
+        blueprints.push(parser.parse_blueprint()?);
+    }
+    Ok(blueprints)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Blueprint, ParseBlueprintError, Recipe, parse_blueprints};
+
+    #[test]
+    fn verify_parse() {
+        let blueprints = parse_blueprints(super::super::INPUT_EXAMPLE).unwrap();
+        assert_eq!(blueprints.len(), 2);
+        assert_eq!(blueprints[1].id, 2);
+        assert_eq!(blueprints[0].resources, vec!["ore", "clay", "obsidian", "geode"]);
+        assert_eq!(blueprints[0].recipes, vec![
+            Recipe { bot: 0, costs: vec![(0, 4)] },
+            Recipe { bot: 1, costs: vec![(0, 2)] },
+            Recipe { bot: 2, costs: vec![(0, 3), (1, 14)] },
+            Recipe { bot: 3, costs: vec![(0, 2), (2, 7)] },
+        ]);
+        assert_eq!(blueprints[0].max_spend(), vec![4, 14, 7, 0]);
+        assert_eq!(parse_blueprints(super::super::INPUT).unwrap().len(), 30);
+    }
+
+    #[test]
+    fn verify_parse_multiline() {
+        // The puzzle description wraps each blueprint over several lines
+        let blueprint: Blueprint = "Blueprint 1:\r\n  Each ore robot costs 4 ore.\r\n  Each clay robot costs 2 ore.\r\n"
+            .parse()
+            .unwrap();
+        assert_eq!(blueprint.recipes.len(), 2);
+
+        // Extra resources and costs with more than two resources
+        let blueprint: Blueprint = "Blueprint 7: Each ore robot costs 1 ore. Each gem robot costs 2 ore and 3 clay and 1 gem."
+            .parse()
+            .unwrap();
+        assert_eq!(blueprint.resources, vec!["ore", "gem", "clay"]);
+        assert_eq!(blueprint.recipes[1], Recipe { bot: 1, costs: vec![(0, 2), (2, 3), (1, 1)] });
+        assert_eq!(blueprint.find_resource("clay"), Some(2));
+        assert_eq!(blueprint.resource_name(1), "gem");
+    }
+
+    #[test]
+    fn verify_parse_errors() {
+        assert_eq!("".parse::<Blueprint>(), Err(ParseBlueprintError { position: 0 }));
+        assert_eq!("Blueprint 1".parse::<Blueprint>(), Err(ParseBlueprintError { position: 11 }));
+        assert_eq!("Blueprint 1: Each ore robot costs ore.".parse::<Blueprint>(), Err(ParseBlueprintError { position: 34 }));
+        assert_eq!("Blueprint 1: Each ore robot costs 4 ore".parse::<Blueprint>(), Err(ParseBlueprintError { position: 39 }));
+        assert_eq!("Blueprint 1: Each ore robot costs 4 ore. Nope".parse::<Blueprint>(), Err(ParseBlueprintError { position: 41 }));
+    }
+}
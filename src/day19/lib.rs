@@ -1,229 +1,359 @@
 use itertools::{Itertools};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use super::blueprint::{Blueprint, Recipe, Resource};
 
-enum Resource {
-    Ore,
-    Clay,
-    Obsidian,
-    _Geode
-}
-
-#[derive(PartialEq, Eq)]
-enum Bot {
-    Orebot,
-    Claybot,
-    Obsidianbot,
-    Geodebot
-}
-
-#[derive(Copy, Clone)]
-pub struct Blueprint {
-    orebot_ore_cost: u16,
-    claybot_ore_cost: u16,
-    obsidanbot_ore_cost: u16,
-    obsidanbot_clay_cost: u16,
-    geodebot_ore_cost: u16,
-    geodebot_obsidian_cost: u16,
-}
-
-#[derive(Copy, Clone)]
 pub struct SimulationState {
-    next_minute: u16,
-
-    // Bots
-    orebot_count: u16,
-    claybot_count: u16,
-    obsidianbot_count: u16,
-    geodebot_count: u16,
-
-    // Resources
-    ore_count: u16,
-    clay_count: u16,
-    obsidian_count: u16,
-    geode_count: u16
+    minutes_elapsed: u16,
+    bots: Vec<u16>,  // Number of bots collecting each resource
+    stock: Vec<u16>  // Amount of each resource collected but not yet spent
 }
 
-fn div_ceil(x: u16, d: u16) -> u16 {
-    (x + d - 1) / d
+// The best build order found for a blueprint. Each step is the minute in which a bot was built (starting from
+// minute 1), matching the puzzle's description of a build order, and the resource that bot collects.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BuildOrder {
+    pub collected: u16, // Amount of the target resource collected by the end
+    pub steps: Vec<(u16, Resource)>
 }
 
 impl SimulationState {
 
-    pub fn new() -> SimulationState {
+    pub fn new(blueprint: &Blueprint, starting_bot: Resource) -> SimulationState {
+        let mut bots = vec![0; blueprint.resources.len()];
+        bots[starting_bot] = 1;
         SimulationState {
-            next_minute: 1,
-            orebot_count: 1,
-            claybot_count: 0,
-            obsidianbot_count: 0,
-            geodebot_count: 0,
-            ore_count: 0,
-            clay_count: 0,
-            obsidian_count: 0,
-            geode_count: 0 }
+            minutes_elapsed: 0,
+            bots,
+            stock: vec![0; blueprint.resources.len()] }
     }
 
     fn minutes_to_gather_resource(&self, needed: u16, resource: Resource) -> Option<u16> {
-        let current;
-        let production_per_min;
-        match resource {
-            Resource::Ore      => {current = self.ore_count; production_per_min = self.orebot_count},
-            Resource::Clay     => {current = self.clay_count; production_per_min = self.claybot_count},
-            Resource::Obsidian => {current = self.obsidian_count; production_per_min = self.obsidianbot_count},
-            Resource::_Geode    => {current = self.geode_count; production_per_min = self.geodebot_count},
+        let remaining_needed = needed.saturating_sub(self.stock[resource]);
+        if remaining_needed == 0 {
+            return Some(0);
         }
 
+        let production_per_min = self.bots[resource];
         if production_per_min == 0 {
             return None;
         }
 
-        let remaining_ore_needed = needed.saturating_sub(current);
-        let minutes_required = div_ceil(remaining_ore_needed, production_per_min as u16);
-        Some(minutes_required)
-    }
-
-    fn minutes_until_can_build_bot(&self, blueprint: &Blueprint, bot: &Bot) -> Option<u16> {
-        match bot {
-            Bot::Orebot =>
-                self.minutes_to_gather_resource(blueprint.orebot_ore_cost, Resource::Ore),
-            Bot::Claybot =>
-                self.minutes_to_gather_resource(blueprint.claybot_ore_cost, Resource::Ore),
-            Bot::Obsidianbot => {
-                let ore_time = self.minutes_to_gather_resource(blueprint.obsidanbot_ore_cost, Resource::Ore);
-                let clay_time = self.minutes_to_gather_resource(blueprint.obsidanbot_clay_cost, Resource::Clay);
-                if ore_time.is_none() || clay_time.is_none() {return None;}
-                Some(ore_time.unwrap().max(clay_time.unwrap()))
-            }
-            Bot::Geodebot => {
-                let ore_time = self.minutes_to_gather_resource(blueprint.geodebot_ore_cost, Resource::Ore);
-                let obsidian_time = self.minutes_to_gather_resource(blueprint.geodebot_obsidian_cost, Resource::Obsidian);
-                if ore_time.is_none() || obsidian_time.is_none() {return None;}
-                Some(ore_time.unwrap().max(obsidian_time.unwrap()))
-            } ,
-
-        }
+        Some(remaining_needed.div_ceil(production_per_min))
     }
 
-    fn build_bot(&mut self, blueprint: &Blueprint, bot: &Bot) {
-        match bot {
-            Bot::Orebot => {
-                self.orebot_count += 1;
-                self.ore_count -= blueprint.orebot_ore_cost;
-            },
-            Bot::Claybot => {
-                self.claybot_count += 1;
-                self.ore_count -= blueprint.claybot_ore_cost;
-            },
-            Bot::Obsidianbot => {
-                self.obsidianbot_count += 1;
-                self.ore_count -= blueprint.obsidanbot_ore_cost;
-                self.clay_count -= blueprint.obsidanbot_clay_cost;
-
-            },
-            Bot::Geodebot => {
-                self.geodebot_count += 1;
-                self.ore_count -= blueprint.geodebot_ore_cost;
-                self.obsidian_count -= blueprint.geodebot_obsidian_cost;
+    fn minutes_until_can_build_bot(&self, recipe: &Recipe) -> Option<u16> {
+        recipe.costs
+            .iter()
+            .map(|&(resource, cost)| self.minutes_to_gather_resource(cost, resource))
+            .fold_options(0, u16::max)
+    }
 
-            }
+    fn build_bot(&mut self, recipe: &Recipe) {
+        self.bots[recipe.bot] += 1;
+        for &(resource, cost) in &recipe.costs {
+            self.stock[resource] -= cost;
         }
     }
 
     // Check if there is any additional benefit in building a bot of the given type.
-    // If we already have enough resource production to build any type of bot, then
-    fn is_useful_to_build_bot(&self, blueprint: &Blueprint, bot: &Bot) -> bool {
-        match bot {
-            Bot::Orebot => {
-                let ore_production = self.orebot_count;
-                ore_production <= (blueprint.claybot_ore_cost).max(blueprint.obsidanbot_ore_cost).max(blueprint.geodebot_ore_cost)
-            }
-            Bot::Claybot => {
-                let clay_production = self.claybot_count;
-                clay_production <= blueprint.obsidanbot_clay_cost
-            },
-            Bot::Obsidianbot => {
-                let obsidian_production = self.obsidianbot_count;
-                obsidian_production <= blueprint.geodebot_obsidian_cost
-            },
-            Bot::Geodebot => true, // We can always use more geodes
-        }
+    // We can only spend so much of a resource each minute, so once the stockpile plus what our bots will collect
+    // covers the most we could spend in every remaining minute, more bots of that type are wasted.
+    fn is_useful_to_build_bot(&self, bot: Resource, max_spend: u16, minutes_remaining: u16) -> bool {
+        let bots = self.bots[bot];
+        bots < max_spend && self.stock[bot] + bots * minutes_remaining < max_spend * minutes_remaining
     }
 
-    fn wait_for_and_build_bot(&self, blueprint: &Blueprint, bot: Bot) -> Option<SimulationState> {
-        let mins_to_wait = self.minutes_until_can_build_bot(blueprint, &bot);
-        mins_to_wait?;
+    fn wait_for_and_build_bot(&mut self, recipe: &Recipe, mins_to_wait: u16) {
+        // Resources are spent at the start of the build minute, but the new bot only starts collecting afterwards
+        self.advance_time(mins_to_wait + 1);
+        self.build_bot(recipe);
+    }
 
-        let mut new = *self;
-        new.advance_time(mins_to_wait.unwrap() as u16 + 1); // Include 1 minute build time.
-        new.build_bot(blueprint, &bot);
-        Some(new)
+    // Reverse `wait_for_and_build_bot`, so the search can backtrack without copying the state for every branch
+    fn undo_wait_for_and_build_bot(&mut self, recipe: &Recipe, mins_to_wait: u16) {
+        self.bots[recipe.bot] -= 1;
+        for &(resource, cost) in &recipe.costs {
+            self.stock[resource] += cost;
+        }
+        for (stock, bots) in self.stock.iter_mut().zip(&self.bots) {
+            *stock -= bots * (mins_to_wait + 1);
+        }
+        self.minutes_elapsed -= mins_to_wait + 1;
     }
 
     fn advance_time(&mut self, minutes: u16) {
-        self.ore_count += (self.orebot_count * minutes) as u16;
-        self.clay_count += (self.claybot_count * minutes) as u16;
-        self.obsidian_count += (self.obsidianbot_count * minutes) as u16;
-        self.geode_count += (self.geodebot_count * minutes) as u16;
-        self.next_minute += minutes;
+        for (stock, bots) in self.stock.iter_mut().zip(&self.bots) {
+            *stock += bots * minutes;
+        }
+        self.minutes_elapsed += minutes;
+    }
+
+    // The amount of the target we end up with if we stop building bots now
+    fn collected_at_end(&self, target: Resource, minutes: u16) -> u16 {
+        self.stock[target] + self.bots[target] * (minutes - self.minutes_elapsed)
+    }
+
+    // An optimistic estimate of the target we could end up with, assuming we could build a bot for it every
+    // remaining minute regardless of resources. The last minute's bot never gets to collect anything.
+    fn upper_bound(&self, target: Resource, minutes: u16) -> u16 {
+        let minutes_remaining = minutes - self.minutes_elapsed;
+        self.collected_at_end(target, minutes) + minutes_remaining * minutes_remaining.saturating_sub(1) / 2
     }
 }
 
-pub fn parse_blueprints(input: &str) -> impl Iterator<Item=Blueprint> + '_ {
-    return input
-        .lines()
-        .map(|line| {
-            let tokens = line.split(' ').collect_vec();
-            Blueprint {
-                orebot_ore_cost: tokens[6].parse().unwrap(),
-                claybot_ore_cost: tokens[12].parse().unwrap(),
-                obsidanbot_ore_cost: tokens[18].parse().unwrap(),
-                obsidanbot_clay_cost: tokens[21].parse().unwrap(),
-                geodebot_ore_cost: tokens[27].parse().unwrap(),
-                geodebot_obsidian_cost: tokens[30].parse().unwrap(),
-            }
-        })
+struct Search<'a> {
+    blueprint: &'a Blueprint,
+    recipe_order: Vec<usize>,
+    max_spend: Vec<u16>,
+    target: Resource,
+    minutes: u16,
+    steps: Vec<(u16, Resource)>,
+    best: BuildOrder
 }
 
-pub fn calculate_blueprint_quality(blueprint: &Blueprint, state: SimulationState, finish_at_minute: u16) -> u16 {
-    // After 32 minutes have been simulated, how many geodes did we collect with this build order?
-    assert!(state.next_minute <= finish_at_minute);
-    if state.next_minute == finish_at_minute {
-        return state.geode_count;
+impl<'a> Search<'a> {
+    fn new(blueprint: &'a Blueprint, target: Resource, minutes: u16) -> Search<'a> {
+        // Try bots for the target first so we find a good build order early and can prune more of the rest.
+        // Later resources tend to be the more valuable ones, so try those next.
+        let recipe_order = (0..blueprint.recipes.len())
+            .rev()
+            .sorted_by_key(|&i| blueprint.recipes[i].bot != target)
+            .collect_vec();
+
+        Search {
+            blueprint,
+            recipe_order,
+            max_spend: blueprint.max_spend(),
+            target,
+            minutes,
+            steps: Vec::new(),
+            best: BuildOrder { collected: 0, steps: Vec::new() }
+        }
+    }
+
+    // Branch and bound over the order in which bots are built, skipping ahead to when each bot can be afforded.
+    fn search(&mut self, state: &mut SimulationState) {
+        // Building nothing more is always an option
+        let collected = state.collected_at_end(self.target, self.minutes);
+        if collected > self.best.collected {
+            self.best.collected = collected;
+            self.best.steps.clone_from(&self.steps);
+        }
+
+        let minutes_remaining = self.minutes - state.minutes_elapsed;
+        for i in 0..self.recipe_order.len() {
+            let recipe = &self.blueprint.recipes[self.recipe_order[i]];
+
+            // Trim build orders that don't gain us anything
+            if recipe.bot != self.target && !state.is_useful_to_build_bot(recipe.bot, self.max_spend[recipe.bot], minutes_remaining) {
+                continue;
+            }
+
+            // A bot finished in the final minute never collects anything, so it must be built before then
+            let Some(mins_to_wait) = state.minutes_until_can_build_bot(recipe) else { continue; };
+            if mins_to_wait + 1 >= minutes_remaining {
+                continue;
+            }
+
+            state.wait_for_and_build_bot(recipe, mins_to_wait);
+            if state.upper_bound(self.target, self.minutes) > self.best.collected {
+                self.steps.push((state.minutes_elapsed, recipe.bot));
+                self.search(state);
+                self.steps.pop();
+            }
+            state.undo_wait_for_and_build_bot(recipe, mins_to_wait);
+        }
     }
+}
+
+// Find the build order that collects the most of the target resource within the given number of minutes,
+// starting with a single bot collecting `starting_bot`
+pub fn optimise_for(blueprint: &Blueprint, starting_bot: Resource, target: Resource, minutes: u16) -> BuildOrder {
+    let mut search = Search::new(blueprint, target, minutes);
+    search.search(&mut SimulationState::new(blueprint, starting_bot));
+    search.best
+}
+
+// Find the build order that opens the most geodes within the given number of minutes, starting with one ore bot
+pub fn optimise(blueprint: &Blueprint, minutes: u16) -> BuildOrder {
+    let ore = blueprint.find_resource("ore").expect("blueprint should use ore");
+    let Some(geode) = blueprint.find_resource("geode") else {
+        // Nothing ever produces or costs geodes
+        return BuildOrder { collected: 0, steps: Vec::new() };
+    };
+    optimise_for(blueprint, ore, geode, minutes)
+}
+
+// Find the best build order for every blueprint, spreading the blueprints across threads.
+// The results are in the same order as the blueprints.
+pub fn optimise_all(blueprints: &[Blueprint], minutes: u16) -> Vec<BuildOrder> {
+    optimise_all_with_progress(blueprints, minutes, |_, _| {})
+}
 
-    let bots = [
-        Bot::Geodebot,
-        Bot::Obsidianbot,
-        Bot::Claybot,
-        Bot::Orebot
-    ];
+// As above, but also calls `on_progress` with the index of each blueprint and its build order as soon as it has
+// been evaluated. Blueprints finish in whatever order the threads get to them, so this may be called out of order.
+pub fn optimise_all_with_progress(
+    blueprints: &[Blueprint],
+    minutes: u16,
+    on_progress: impl Fn(usize, &BuildOrder) + Sync
+) -> Vec<BuildOrder> {
+    let num_threads = thread::available_parallelism().map_or(1, |n| n.get()).min(blueprints.len());
+    let next_blueprint = AtomicUsize::new(0);
 
-    let mut could_build_any_bots = false;
-    let mut highest_score = 0;
-    for bot in bots {
+    let mut results = thread::scope(|scope| {
+        let workers = (0..num_threads)
+            .map(|_| scope.spawn(|| {
+                // Each worker takes the next blueprint nobody has started yet, so slow blueprints don't hold up the rest
+                let mut results = Vec::new();
+                loop {
+                    let i = next_blueprint.fetch_add(1, Ordering::Relaxed);
+                    let Some(blueprint) = blueprints.get(i) else { break; };
+                    let build_order = optimise(blueprint, minutes);
+                    on_progress(i, &build_order);
+                    results.push((i, build_order));
+                }
+                results
+            }))
+            .collect_vec();
 
-        // Trim build orders that don't gain us anything
-        if ! state.is_useful_to_build_bot(blueprint, &bot) {
-            continue;
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().unwrap())
+            .collect_vec()
+    });
+
+    results.sort_by_key(|(i, _)| *i);
+    results.into_iter().map(|(_, build_order)| build_order).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+    use std::sync::Mutex;
+    use super::{BuildOrder, optimise, optimise_all, optimise_all_with_progress, optimise_for};
+    use super::super::blueprint::{Blueprint, Resource, parse_blueprints};
+
+    // Replay a build order minute by minute, returning the target collected or None if a bot couldn't be afforded
+    fn replay(blueprint: &Blueprint, steps: &[(u16, Resource)], starting_bot: Resource, target: Resource, minutes: u16) -> Option<u16> {
+        let mut bots = vec![0; blueprint.resources.len()];
+        bots[starting_bot] = 1;
+        let mut stock = vec![0u16; blueprint.resources.len()];
+        let mut steps = steps.iter().peekable();
+        for minute in 1..=minutes {
+            let building = steps.next_if(|(step_minute, _)| *step_minute == minute).map(|(_, bot)| *bot);
+            if let Some(bot) = building {
+                let recipe = blueprint.recipes.iter().find(|recipe| recipe.bot == bot).unwrap();
+                for &(resource, cost) in &recipe.costs {
+                    stock[resource] = stock[resource].checked_sub(cost)?;
+                }
+            }
+            for (stock, count) in stock.iter_mut().zip(&bots) {
+                *stock += count;
+            }
+            if let Some(bot) = building {
+                bots[bot] += 1;
+            }
         }
+        assert!(steps.next().is_none());
+        Some(stock[target])
+    }
 
-        // What happens if we build this bot next?
-        let new_state = state.wait_for_and_build_bot(blueprint, bot);
-        if new_state.is_none() { continue; } // We can't build this bot by waiting any amount of time
-        if new_state.unwrap().next_minute > finish_at_minute { continue; } // We can't build this bot in time
+    // Try every order of building bots without any pruning
+    fn exhaustive(blueprint: &Blueprint, steps: &mut Vec<(u16, Resource)>, target: Resource, minutes: u16) -> u16 {
+        let mut best = replay(blueprint, steps, 0, target, minutes).unwrap();
+        let next_minute = steps.last().map_or(1, |(minute, _)| minute + 1);
+        for minute in next_minute..minutes {
+            for recipe in &blueprint.recipes {
+                steps.push((minute, recipe.bot));
+                if replay(blueprint, steps, 0, target, minute).is_some() {
+                    best = best.max(exhaustive(blueprint, steps, target, minutes));
+                }
+                steps.pop();
+            }
+        }
+        best
+    }
+
+    #[test]
+    fn verify_example_build_order() {
+        let blueprints = parse_blueprints(super::super::INPUT_EXAMPLE).unwrap();
+        let build_order = optimise(&blueprints[0], 24);
+        assert_eq!(build_order.collected, 9);
+        assert_eq!(replay(&blueprints[0], &build_order.steps, 0, 3, 24), Some(9));
+
+        // The build order from the puzzle description is just as good
+        let (clay, obsidian, geode) = (1, 2, 3);
+        let example = [
+            (3, clay),
+            (5, clay),
+            (7, clay),
+            (11, obsidian),
+            (12, clay),
+            (15, obsidian),
+            (18, geode),
+            (21, geode),
+        ];
+        assert_eq!(replay(&blueprints[0], &example, 0, geode, 24), Some(9));
+    }
+
+    #[test]
+    fn verify_minutes() {
+        let blueprints = parse_blueprints(super::super::INPUT_EXAMPLE).unwrap();
+        for (blueprint, expected) in blueprints.iter().zip([56, 62]) {
+            let build_order = optimise(blueprint, 32);
+            assert_eq!(build_order.collected, expected);
+            assert_eq!(replay(blueprint, &build_order.steps, 0, 3, 32), Some(expected));
+            assert!(build_order.steps.iter().all(|(minute, _)| (1..32).contains(minute)));
+        }
 
-        // Continue to simulate if we assume this decision
-        could_build_any_bots = true;
-        let highest_score_from_this_path = calculate_blueprint_quality(blueprint, new_state.unwrap(), finish_at_minute);
-        highest_score = highest_score.max(highest_score_from_this_path);
+        // Not enough time to build anything useful
+        assert_eq!(optimise(&blueprints[0], 1), BuildOrder { collected: 0, steps: Vec::new() });
     }
 
-    if could_build_any_bots {
-        highest_score
-    } else {
-        // No time left to build any more bots, so let's just simulate resource collection until the end
-        let minutes_remaining = finish_at_minute - state.next_minute;
-        let mut end_state = state;
-        end_state.advance_time(minutes_remaining);
-        calculate_blueprint_quality(blueprint, end_state, finish_at_minute)
+    #[test]
+    fn verify_extra_resources() {
+        // An extra resource and costs with more than two resources
+        let blueprint: Blueprint = "Blueprint 1: \
+            Each ore robot costs 2 ore. \
+            Each clay robot costs 2 ore. \
+            Each crystal robot costs 1 ore and 3 clay. \
+            Each geode robot costs 1 ore and 2 clay and 2 crystal."
+            .parse()
+            .unwrap();
+        let geode = blueprint.find_resource("geode").unwrap();
+
+        for minutes in [10, 12, 13] {
+            let build_order = optimise(&blueprint, minutes);
+            assert_eq!(build_order.collected, exhaustive(&blueprint, &mut Vec::new(), geode, minutes));
+            assert_eq!(replay(&blueprint, &build_order.steps, 0, geode, minutes), Some(build_order.collected));
+        }
+        assert!(optimise(&blueprint, 13).collected > 0);
+
+        // Any resource can be the target
+        let clay = blueprint.find_resource("clay").unwrap();
+        let build_order = optimise_for(&blueprint, 0, clay, 10);
+        assert_eq!(build_order.collected, exhaustive(&blueprint, &mut Vec::new(), clay, 10));
     }
 
-}
\ No newline at end of file
+    #[test]
+    fn verify_optimise_all() {
+        let blueprints = parse_blueprints(super::super::INPUT).unwrap();
+        let sequential = blueprints.iter().map(|blueprint| optimise(blueprint, 24)).collect_vec();
+        assert_eq!(optimise_all(&blueprints, 24), sequential);
+
+        // Progress is reported exactly once for each blueprint
+        let reported = Mutex::new(Vec::new());
+        let parallel = optimise_all_with_progress(&blueprints, 24, |i, build_order| {
+            reported.lock().unwrap().push((i, build_order.collected));
+        });
+        assert_eq!(parallel, sequential);
+
+        let mut reported = reported.into_inner().unwrap();
+        reported.sort();
+        let expected = sequential.iter().map(|build_order| build_order.collected).enumerate().collect_vec();
+        assert_eq!(reported, expected);
+
+        assert!(optimise_all(&[], 24).is_empty());
+    }
+}
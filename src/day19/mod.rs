@@ -1,4 +1,5 @@
 pub mod lib;
+pub mod blueprint;
 pub mod part1;
 pub mod part2;
 
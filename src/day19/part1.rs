@@ -1,16 +1,13 @@
-use crate::day19::lib::{SimulationState, calculate_blueprint_quality};
-use super::lib::parse_blueprints;
+use super::blueprint::parse_blueprints;
+use super::lib::optimise_all;
 
 pub fn solve(input: &str) -> i32 {
-    let blueprints = parse_blueprints(input);
+    let blueprints = parse_blueprints(input).unwrap();
 
-    blueprints
-        .enumerate()
-        .map(|(i, blueprint)| {
-            let quality = calculate_blueprint_quality(&blueprint, SimulationState::new(), 25);
-            println!("Blueprint {} resulted in {} geodes", i + 1, quality);
-            (i as i32+1) * quality as i32
-        })
+    optimise_all(&blueprints, 24)
+        .iter()
+        .zip(&blueprints)
+        .map(|(build_order, blueprint)| blueprint.id as i32 * build_order.collected as i32)
         .sum()
 }
 
@@ -1,18 +1,13 @@
-use crate::day19::lib::{SimulationState, calculate_blueprint_quality};
-use super::lib::parse_blueprints;
+use super::blueprint::parse_blueprints;
+use super::lib::optimise_all;
 
 pub fn solve(input: &str) -> i32 {
-    let blueprints = parse_blueprints(input);
+    let blueprints = parse_blueprints(input).unwrap();
 
-    blueprints
-        .take(3)
-        .enumerate()
-        .map(|(i, blueprint)| {
-            let quality = calculate_blueprint_quality(&blueprint, SimulationState::new(), 33);
-            println!("Blueprint {} resulted in {} geodes", i + 1, quality);
-            quality
-        })
-        .product::<u16>() as i32
+    optimise_all(&blueprints[..3.min(blueprints.len())], 32)
+        .iter()
+        .map(|build_order| build_order.collected as i32)
+        .product()
 }
 
 pub mod tests {
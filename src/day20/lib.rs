@@ -0,0 +1,239 @@
+use std::str::FromStr;
+use num::{PrimInt, Signed};
+
+// The positions after the number 0 that make up the grove coordinates
+pub const GROVE_OFFSETS: [usize; 3] = [1000, 2000, 3000];
+
+// A circular list of numbers that can each be moved forwards or backwards by some number of positions.
+//
+// Elements are identified by their original index, and are kept in order across a list of buckets of roughly
+// √n elements each. Finding an element's position, removing it and inserting it elsewhere each only touch the
+// bucket lengths and a single bucket, so a move costs O(√n) instead of the O(n) of shifting a single `Vec`.
+pub struct MixingList<T> {
+    values: Vec<T>,            // Indexed by original index
+    buckets: Vec<Vec<usize>>,  // Original indices in list order
+    bucket_of: Vec<usize>,     // Which bucket each original index is currently in
+    max_bucket_len: usize
+}
+
+impl<T> MixingList<T> {
+    pub fn new(values: Vec<T>) -> MixingList<T> {
+        let bucket_len = (values.len() as f64).sqrt().ceil().max(1.0) as usize;
+        let buckets = (0..values.len())
+            .collect::<Vec<_>>()
+            .chunks(bucket_len)
+            .map(|chunk| chunk.to_vec())
+            .collect::<Vec<_>>();
+        let bucket_of = (0..values.len()).map(|i| i / bucket_len).collect();
+
+        MixingList { values, buckets, bucket_of, max_bucket_len: bucket_len * 2 }
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    // The value that was originally at the given index
+    pub fn value(&self, original_i: usize) -> &T {
+        &self.values[original_i]
+    }
+
+    // The current position of the element that was originally at the given index
+    pub fn position(&self, original_i: usize) -> usize {
+        let bucket = self.bucket_of[original_i];
+        let before: usize = self.buckets[..bucket].iter().map(Vec::len).sum();
+        before + self.buckets[bucket].iter().position(|&i| i == original_i).unwrap()
+    }
+
+    // The value currently at the given position
+    pub fn get(&self, position: usize) -> &T {
+        let (bucket, offset) = self.locate(position);
+        &self.values[self.buckets[bucket][offset]]
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+        self.buckets.iter().flatten().map(|&i| &self.values[i])
+    }
+
+    // Move the element originally at the given index by `steps` positions, wrapping around the ends.
+    // The element is lifted out before moving, so it wraps around the other `len() - 1` elements.
+    pub fn move_by(&mut self, original_i: usize, steps: i64) {
+        if self.len() <= 1 {
+            return;
+        }
+
+        let position = self.position(original_i);
+        let destination = (position as i64 + steps).rem_euclid(self.len() as i64 - 1) as usize;
+        if destination == position {
+            return;
+        }
+
+        self.remove(original_i);
+        self.insert(destination, original_i);
+    }
+
+    // Find the bucket and offset within it for a position
+    fn locate(&self, mut position: usize) -> (usize, usize) {
+        for (bucket, indices) in self.buckets.iter().enumerate() {
+            if position < indices.len() {
+                return (bucket, position);
+            }
+            position -= indices.len();
+        }
+        panic!("Position is past the end of the list");
+    }
+
+    // Renumber the buckets from the given one onwards, after buckets have been added or removed
+    fn update_bucket_of(&mut self, from_bucket: usize) {
+        for (bucket, indices) in self.buckets.iter().enumerate().skip(from_bucket) {
+            for &i in indices {
+                self.bucket_of[i] = bucket;
+            }
+        }
+    }
+
+    fn remove(&mut self, original_i: usize) {
+        let bucket = self.bucket_of[original_i];
+        self.buckets[bucket].retain(|&i| i != original_i);
+
+        // Drop empty buckets so we don't end up walking lots of them
+        if self.buckets[bucket].is_empty() && self.buckets.len() > 1 {
+            self.buckets.remove(bucket);
+            self.update_bucket_of(bucket);
+        }
+    }
+
+    fn insert(&mut self, position: usize, original_i: usize) {
+        let (bucket, offset) = self.locate(position);
+        self.buckets[bucket].insert(offset, original_i);
+        self.bucket_of[original_i] = bucket;
+
+        // Split buckets that grow too large, so every operation stays O(√n)
+        if self.buckets[bucket].len() > self.max_bucket_len {
+            let second_half = self.buckets[bucket].split_off(self.max_bucket_len / 2);
+            self.buckets.insert(bucket + 1, second_half);
+            self.update_bucket_of(bucket + 1);
+        }
+    }
+}
+
+impl<T: PrimInt> MixingList<T> {
+    // Move every number by its own value, in the order they were originally in
+    pub fn mix(&mut self) {
+        // Moving by a multiple of `len() - 1` ends up in the same place, so reduce huge values down to fit in an i64
+        let modulus = T::from(self.len().saturating_sub(1).max(1));
+        for original_i in 0..self.len() {
+            let n = self.values[original_i];
+            let steps = match modulus {
+                Some(modulus) => n % modulus,
+                None => n // The list is longer than any value of this width, so there's nothing to reduce
+            };
+            self.move_by(original_i, steps.to_i64().unwrap());
+        }
+    }
+
+    // The numbers at each offset after the number 0, wrapping around the list
+    pub fn grove_coordinates(&self, offsets: &[usize]) -> Vec<T> {
+        let zero_i = self.position(self.values.iter().position(|n| n.is_zero()).unwrap());
+        offsets
+            .iter()
+            .map(|offset| *self.get((zero_i + offset) % self.len()))
+            .collect()
+    }
+}
+
+// Apply the decryption key to every number and mix the list the given number of rounds.
+// Returns None if any number, or its product with the key, doesn't fit in the chosen integer width.
+pub fn decrypt<T: PrimInt + Signed + FromStr>(input: &str, key: T, rounds: usize) -> Option<MixingList<T>> {
+    let values = input
+        .lines()
+        .map(|line| line.parse::<T>().ok()?.checked_mul(&key))
+        .collect::<Option<Vec<T>>>()?;
+
+    let mut list = MixingList::new(values);
+    for _ in 0..rounds {
+        list.mix();
+    }
+    Some(list)
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+    use oorandom::Rand32;
+    use super::{GROVE_OFFSETS, MixingList, decrypt};
+
+    // The straightforward version, shifting everything in a single `Vec`
+    fn move_by_naive(list: &mut Vec<usize>, original_i: usize, steps: i64) {
+        let position = list.iter().position(|&i| i == original_i).unwrap();
+        let destination = (position as i64 + steps).rem_euclid(list.len() as i64 - 1) as usize;
+        list.remove(position);
+        list.insert(destination, original_i);
+    }
+
+    #[test]
+    fn verify_example_round() {
+        let values = [1, 2, -3, 3, -2, 0, 4];
+        let mut list = MixingList::new(values.to_vec());
+        for i in 0..list.len() {
+            list.move_by(i, *list.value(i));
+        }
+        assert_eq!(list.iter().copied().collect_vec(), vec![-2, 1, 2, -3, 4, 0, 3]);
+        assert_eq!(list.position(5), 5);
+        assert_eq!(*list.get(4), 4);
+    }
+
+    #[test]
+    fn verify_against_naive() {
+        let mut rng = Rand32::new(2022);
+        for len in [1, 2, 3, 10, 101, 500] {
+            let mut list = MixingList::new((0..len).collect_vec());
+            let mut naive = (0..len).collect_vec();
+            for _ in 0..2000 {
+                let i = rng.rand_range(0..len as u32) as usize;
+                let steps = rng.rand_range(0..4 * len as u32) as i64 - 2 * len as i64;
+                list.move_by(i, steps);
+                if len > 1 {
+                    move_by_naive(&mut naive, i, steps);
+                }
+            }
+            assert_eq!(list.iter().copied().collect_vec(), naive);
+            for (position, &i) in naive.iter().enumerate() {
+                assert_eq!(list.position(i), position);
+                assert_eq!(*list.get(position), i);
+            }
+        }
+    }
+
+    #[test]
+    fn verify_decrypt() {
+        let coordinates = decrypt::<i32>(super::super::INPUT_EXAMPLE, 1, 1).unwrap().grove_coordinates(&GROVE_OFFSETS);
+        assert_eq!(coordinates, vec![4, -3, 2]);
+        let coordinates = decrypt::<i64>(super::super::INPUT_EXAMPLE, 811589153, 10).unwrap().grove_coordinates(&GROVE_OFFSETS);
+        assert_eq!(coordinates, vec![811589153, 2434767459, -1623178306]);
+        assert_eq!(decrypt::<i16>(super::super::INPUT_EXAMPLE, 1, 1).unwrap().grove_coordinates(&[0, 1, 7]), vec![0, 3, 0]);
+    }
+
+    #[test]
+    fn verify_widths_agree() {
+        let input = super::super::INPUT;
+        let part1 = decrypt::<i32>(input, 1, 1).unwrap().grove_coordinates(&GROVE_OFFSETS);
+        let part1_wide = decrypt::<i128>(input, 1, 1).unwrap().grove_coordinates(&GROVE_OFFSETS);
+        assert_eq!(part1.iter().map(|&n| n as i128).collect_vec(), part1_wide);
+
+        let part2 = decrypt::<i64>(input, 811589153, 10).unwrap().grove_coordinates(&GROVE_OFFSETS);
+        let part2_wide = decrypt::<i128>(input, 811589153, 10).unwrap().grove_coordinates(&GROVE_OFFSETS);
+        assert_eq!(part2.iter().map(|&n| n as i128).collect_vec(), part2_wide);
+
+        // The decryption key overflows narrower integers
+        assert!(decrypt::<i32>(input, 811589153, 10).is_none());
+        assert!(decrypt::<i16>(super::super::INPUT_EXAMPLE, 20000, 1).is_none());
+        assert!(decrypt::<i16>(super::super::INPUT_EXAMPLE, 8000, 1).is_some());
+        // The input itself doesn't fit in an i8
+        assert!(decrypt::<i8>(input, 1, 1).is_none());
+    }
+}
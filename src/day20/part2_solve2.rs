@@ -0,0 +1,29 @@
+use super::lib::{GROVE_OFFSETS, decrypt};
+
+pub fn solve(input: &str) -> i64 {
+    let decrypted = decrypt::<i64>(input, 811589153, 10).unwrap();
+    decrypted.grove_coordinates(&GROVE_OFFSETS).iter().sum()
+}
+
+pub mod tests {
+    use criterion::Criterion;
+    use std::path::Path;
+
+    #[test]
+    fn verify_example() {
+        assert_eq!(super::solve(super::super::INPUT_EXAMPLE), 1623178306);
+    }
+
+    #[test]
+    fn verify_solution() {
+        assert_eq!(super::solve(super::super::INPUT), 1428396909280);
+    }
+
+    pub fn benchmark(c: &mut Criterion) {
+        let path = Path::new(file!());
+        let day = path.parent().unwrap().to_str().unwrap();
+        let solution = path.file_stem().unwrap().to_str().unwrap();
+        let id = day.to_owned() + "_" + solution; // dayX_partY_solveZ
+        c.bench_function( &id, |b| b.iter(|| super::solve(super::super::INPUT)));
+    }
+}
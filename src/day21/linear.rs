@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+use num::rational::Ratio;
+use num::{One, Zero};
+use super::lib::{Monkey, Operation};
+
+// An expression of the form `coefficient * x + constant`, where x is the number we need to yell.
+// Coefficients are kept as exact fractions, so dividing along the way never loses anything.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Linear {
+    coefficient: Ratio<i128>,
+    constant: Ratio<i128>
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum SolveError {
+    NonLinear,                // The unknown ends up multiplied by itself, or we divide by it
+    DivideByZero,
+    NoSolution,               // Both sides always differ, whatever we yell
+    InfiniteSolutions,        // Both sides are always equal, whatever we yell
+    NotInteger(Ratio<i128>),  // The only solution is a fraction
+    OutOfRange(i128)          // The solution doesn't fit in an i64
+}
+
+impl Linear {
+    fn constant(value: i64) -> Linear {
+        Linear { coefficient: Ratio::zero(), constant: Ratio::from_integer(value as i128) }
+    }
+
+    fn unknown() -> Linear {
+        Linear { coefficient: Ratio::one(), constant: Ratio::zero() }
+    }
+
+    fn is_constant(&self) -> bool {
+        self.coefficient.is_zero()
+    }
+
+    fn scale(&self, factor: Ratio<i128>) -> Linear {
+        Linear { coefficient: self.coefficient * factor, constant: self.constant * factor }
+    }
+
+    fn apply(&self, operation: Operation, other: &Linear) -> Result<Linear, SolveError> {
+        match operation {
+            Operation::Add => Ok(Linear {
+                coefficient: self.coefficient + other.coefficient,
+                constant: self.constant + other.constant
+            }),
+            Operation::Subtract => Ok(Linear {
+                coefficient: self.coefficient - other.coefficient,
+                constant: self.constant - other.constant
+            }),
+            Operation::Multiply => {
+                if self.is_constant() {
+                    Ok(other.scale(self.constant))
+                } else if other.is_constant() {
+                    Ok(self.scale(other.constant))
+                } else {
+                    Err(SolveError::NonLinear)
+                }
+            },
+            Operation::Divide => {
+                if !other.is_constant() {
+                    Err(SolveError::NonLinear)
+                } else if other.constant.is_zero() {
+                    Err(SolveError::DivideByZero)
+                } else {
+                    Ok(self.scale(other.constant.recip()))
+                }
+            }
+        }
+    }
+}
+
+fn build_expression(monkey_name: &str, unknown: &str, monkeys: &HashMap<&str, Monkey>) -> Result<Linear, SolveError> {
+    if monkey_name == unknown {
+        return Ok(Linear::unknown());
+    }
+
+    match monkeys.get(monkey_name).unwrap() {
+        Monkey::Operation(o) => {
+            let left = build_expression(o.left, unknown, monkeys)?;
+            let right = build_expression(o.right, unknown, monkeys)?;
+            left.apply(o.operation, &right)
+        },
+        Monkey::Constant(m) => Ok(Linear::constant(m.value)),
+    }
+}
+
+// Find the number the `unknown` monkey must yell so that both children of `root` are equal.
+// Each monkey's number is built up as a linear expression of the unknown in a single pass over the tree, then the
+// resulting equation is rearranged to find the unknown exactly.
+pub fn solve_for(monkeys: &HashMap<&str, Monkey>, root: &str, unknown: &str) -> Result<i64, SolveError> {
+    let Monkey::Operation(o) = monkeys.get(root).unwrap() else {
+        panic!("The root monkey should compare two other monkeys");
+    };
+    let left = build_expression(o.left, unknown, monkeys)?;
+    let right = build_expression(o.right, unknown, monkeys)?;
+
+    // left.coefficient * x + left.constant = right.coefficient * x + right.constant
+    let coefficient = left.coefficient - right.coefficient;
+    let constant = right.constant - left.constant;
+    if coefficient.is_zero() {
+        return Err(if constant.is_zero() { SolveError::InfiniteSolutions } else { SolveError::NoSolution });
+    }
+
+    let x = constant / coefficient;
+    if !x.is_integer() {
+        return Err(SolveError::NotInteger(x));
+    }
+    let x = x.to_integer();
+    i64::try_from(x).map_err(|_| SolveError::OutOfRange(x))
+}
+
+#[cfg(test)]
+mod tests {
+    use num::rational::Ratio;
+    use super::{SolveError, solve_for};
+    use super::super::lib::parse_monkeys;
+
+    fn solve(input: &str) -> Result<i64, SolveError> {
+        solve_for(&parse_monkeys(input), "root", "humn")
+    }
+
+    #[test]
+    fn verify_example() {
+        assert_eq!(solve(super::super::INPUT_EXAMPLE), Ok(301));
+        // Any monkey can be the unknown
+        assert_eq!(solve_for(&parse_monkeys(super::super::INPUT_EXAMPLE), "root", "dvpt"), Ok(-293));
+    }
+
+    #[test]
+    fn verify_errors() {
+        let input = "root: aaaa + bbbb\nhumn: 5\ntwoo: 2\nzero: 0\nthre: 3\n";
+        assert_eq!(solve(&(input.to_owned() + "aaaa: humn * twoo\nbbbb: thre * thre\n")), Err(SolveError::NotInteger(Ratio::new(9, 2))));
+        assert_eq!(solve(&(input.to_owned() + "aaaa: humn / twoo\nbbbb: thre * thre\n")), Ok(18));
+        assert_eq!(solve(&(input.to_owned() + "aaaa: humn * humn\nbbbb: thre * thre\n")), Err(SolveError::NonLinear));
+        assert_eq!(solve(&(input.to_owned() + "aaaa: thre / humn\nbbbb: thre * thre\n")), Err(SolveError::NonLinear));
+        assert_eq!(solve(&(input.to_owned() + "aaaa: humn / zero\nbbbb: thre * thre\n")), Err(SolveError::DivideByZero));
+        assert_eq!(solve(&(input.to_owned() + "aaaa: humn * zero\nbbbb: thre * thre\n")), Err(SolveError::NoSolution));
+        assert_eq!(solve(&(input.to_owned() + "aaaa: humn - humn\nbbbb: thre - thre\n")), Err(SolveError::InfiniteSolutions));
+    }
+}
@@ -1,6 +1,8 @@
 pub mod lib;
+pub mod linear;
 pub mod part1;
-pub mod part2;
+pub mod part2_solve1;
+pub mod part2_solve2;
 
 pub static INPUT: &str = include_str!("./input.txt");
 pub static INPUT_EXAMPLE: &str = include_str!("./input_example.txt");
@@ -1,3 +1,5 @@
+pub mod util;
+
 pub mod day1;
 pub mod day2;
 pub mod day3;
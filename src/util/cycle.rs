@@ -0,0 +1,196 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+// A metric that accumulates over steps (e.g. tower height, inspection counts), which lets us
+// extrapolate its value once the underlying state is known to repeat.
+pub trait CumulativeMetric: Clone {
+    // Returns `self + (to - from) * times`. Unsigned metrics must not decrease from `from` to `to`.
+    fn advance(&self, from: &Self, to: &Self, times: usize) -> Self;
+}
+
+impl CumulativeMetric for usize {
+    fn advance(&self, from: &Self, to: &Self, times: usize) -> Self {
+        self + to.checked_sub(*from).expect("Metric decreased over the cycle") * times
+    }
+}
+
+impl CumulativeMetric for u64 {
+    fn advance(&self, from: &Self, to: &Self, times: usize) -> Self {
+        self + to.checked_sub(*from).expect("Metric decreased over the cycle") * times as u64
+    }
+}
+
+impl CumulativeMetric for i64 {
+    fn advance(&self, from: &Self, to: &Self, times: usize) -> Self {
+        self + (to - from) * times as i64
+    }
+}
+
+impl<T: CumulativeMetric> CumulativeMetric for Vec<T> {
+    fn advance(&self, from: &Self, to: &Self, times: usize) -> Self {
+        self.iter()
+            .zip(from.iter().zip(to.iter()))
+            .map(|(value, (from, to))| value.advance(from, to, times))
+            .collect()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Strategy {
+    // Remember every state seen. Finds the exact start of the cycle on the first repeat.
+    HashMap,
+    // Brent's variant of Floyd's tortoise and hare. Only keeps a single state in memory, but may
+    // take up to twice as many steps to confirm the cycle. Finding the exact start would mean
+    // replaying the steps from the beginning, so it reports a later step inside the cycle instead.
+    Brent,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cycle {
+    // A step from which the states repeat every `lambda` steps. This is the first step of the cycle
+    // (μ) with `Strategy::HashMap`, but can be any later step with `Strategy::Brent`.
+    pub start: usize,
+    pub lambda: usize, // Number of steps in each cycle
+}
+
+pub struct CycleDetector<S, M> {
+    strategy: Strategy,
+    metrics: Vec<M>,
+    cycle: Option<Cycle>,
+    seen_states: HashMap<S, usize>,
+    // Brent state
+    tortoise: Option<(S, usize)>,
+    power: usize,
+    lambda: usize,
+}
+
+impl<S: Hash + Eq, M: CumulativeMetric> CycleDetector<S, M> {
+    pub fn new(strategy: Strategy) -> CycleDetector<S, M> {
+        CycleDetector {
+            strategy,
+            metrics: Vec::new(),
+            cycle: None,
+            seen_states: HashMap::new(),
+            tortoise: None,
+            power: 1,
+            lambda: 0,
+        }
+    }
+
+    pub fn cycle(&self) -> Option<Cycle> {
+        self.cycle
+    }
+
+    pub fn steps(&self) -> usize {
+        self.metrics.len()
+    }
+
+    // Record the state and metric after the next step.
+    // Returns the cycle once it has been found, after which further steps are ignored.
+    pub fn record(&mut self, state: S, metric: M) -> Option<Cycle> {
+        if self.cycle.is_some() {
+            return self.cycle;
+        }
+
+        let step = self.metrics.len();
+        self.metrics.push(metric);
+
+        self.cycle = match self.strategy {
+            Strategy::HashMap => self.record_hash_map(state, step),
+            Strategy::Brent => self.record_brent(state, step),
+        };
+        self.cycle
+    }
+
+    fn record_hash_map(&mut self, state: S, step: usize) -> Option<Cycle> {
+        self.seen_states.insert(state, step).map(|start| Cycle { start, lambda: step - start })
+    }
+
+    fn record_brent(&mut self, state: S, step: usize) -> Option<Cycle> {
+        let Some((tortoise, tortoise_step)) = &self.tortoise else {
+            self.tortoise = Some((state, step));
+            return None;
+        };
+
+        self.lambda += 1;
+        if *tortoise == state {
+            return Some(Cycle { start: *tortoise_step, lambda: self.lambda });
+        }
+
+        // Teleport the tortoise to the hare each time the search window doubles
+        if self.power == self.lambda {
+            self.tortoise = Some((state, step));
+            self.power *= 2;
+            self.lambda = 0;
+        }
+        None
+    }
+
+    // Find the value of the metric after the given step, extrapolating past the recorded steps
+    // using the cycle if we've found one.
+    pub fn extrapolate(&self, step: usize) -> Option<M> {
+        if step < self.metrics.len() {
+            return Some(self.metrics[step].clone());
+        }
+
+        let Cycle { start, lambda } = self.cycle?;
+        let full_cycles = (step - start) / lambda;
+        let remainder = (step - start) % lambda;
+        let cycle_start = &self.metrics[start];
+        let cycle_end = &self.metrics[start + lambda];
+        Some(self.metrics[start + remainder].advance(cycle_start, cycle_end, full_cycles))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Steps through 0, 1, 2, 3, 4, 5, 2, 3, 4, 5, ... with the metric counting steps taken
+    fn run(strategy: Strategy) -> CycleDetector<usize, usize> {
+        let mut detector = CycleDetector::new(strategy);
+        let mut state = 0;
+        for step in 0.. {
+            if detector.record(state, step + 1).is_some() {
+                break;
+            }
+            state = if state == 5 { 2 } else { state + 1 };
+        }
+        detector
+    }
+
+    #[test]
+    fn verify_hash_map() {
+        let detector = run(Strategy::HashMap);
+        assert_eq!(detector.cycle(), Some(Cycle { start: 2, lambda: 4 }));
+        assert_eq!(detector.extrapolate(1_000_000), Some(1_000_001));
+    }
+
+    #[test]
+    fn verify_brent() {
+        let detector = run(Strategy::Brent);
+        let cycle = detector.cycle().unwrap();
+        assert_eq!(cycle.lambda, 4);
+        // The tortoise last teleported to step 3, and the hare met it one cycle later
+        assert_eq!(cycle.start, 3);
+        assert_eq!(detector.extrapolate(1_000_000), Some(1_000_001));
+    }
+
+    #[test]
+    #[should_panic(expected = "Metric decreased over the cycle")]
+    fn verify_decreasing_metric() {
+        let mut detector = CycleDetector::new(Strategy::HashMap);
+        detector.record('a', 5usize);
+        detector.record('a', 3);
+        detector.extrapolate(10);
+    }
+
+    #[test]
+    fn verify_vec_metric() {
+        let mut detector = CycleDetector::new(Strategy::HashMap);
+        detector.record('a', vec![1usize, 0]);
+        detector.record('b', vec![1, 1]);
+        detector.record('a', vec![2, 1]);
+        assert_eq!(detector.extrapolate(10), Some(vec![6, 5]));
+    }
+}